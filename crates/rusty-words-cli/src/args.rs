@@ -1,11 +1,15 @@
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, builder::RangedU64ValueParser};
 
-use rusty_words_common::judgement::TryMethod;
+use rusty_words_common::formats::ListFormat;
+use rusty_words_common::judgement::{JudgementArgs, TryMethod};
+use rusty_words_common::lang_codes::LanguageTag;
 // HACK: ImportArgs is now in common, but the rest of the args parsing is not because ImportArgs is
 // consumed by `common::import_list`.
-use rusty_words_common::model::{ImportArgs, WordsDirection};
+use rusty_words_common::model::{ImportArgs, ListHandle, WordsDirection};
+use rusty_words_common::practice::PracticeMode;
+use rusty_words_common::store::LockKind;
 
 #[derive(Parser, Debug, Clone)]
 #[clap(about, author, version)]
@@ -18,29 +22,75 @@ pub struct Cli {
 pub enum Command {
     /// Create a new words list
     New(NewArgs),
-    /// Import an existing words list (tsv or ron)
+    /// Import an existing words list (tsv, csv, json or ron)
     Import(ImportArgs),
     /// List all existing words lists
     Ls(ListArgs),
-    /// Show all information about a words list by ID
+    /// Show all information about a words list by ID, UUID or name
     Show(ShowArgs),
-    /// Edit an existing words list by ID
-    Edit { id: usize },
-    /// Learn word list by ID
+    /// Edit an existing words list by ID, UUID or name
+    Edit { id: ListHandle },
+    /// Export a words list by ID, UUID or name (tsv, csv, json or ron)
+    Export(ExportArgs),
+    /// Learn word lists by ID, UUID or name. Where you left off is only saved when practicing all
+    /// words of a single list
     Try(TryArgs),
-    /// Delete word list by ID
+    /// Review the words of all lists which are due, using spaced repetition
+    Review(ReviewArgs),
+    /// Delete word list by ID, (a prefix of) its UUID or its exact name
     Rm(RmArgs),
     /// Removes all words lists in the store that are not currently in the index
     GarbageCollect(GCArgs),
+    /// Configure how you are judged, for a list or globally
+    Configure(ConfigureArgs),
+    /// Show statistics of your practice sessions, for a list or for all lists
+    Stats(StatsArgs),
+    /// Move lists to a folder, use `/` to move them out of their folder. The lists are referred to by
+    /// ID, (a prefix of) their UUID or their exact name.
+    Mv(MvArgs),
+    /// Create a new (empty) folder
+    Mkdir { folder: PathBuf },
+    /// Rename or move a folder, together with everything in it
+    RenameFolder { from: PathBuf, to: PathBuf },
+    /// Remove a folder, moving everything in it to the folder it was in
+    Rmdir { folder: PathBuf },
+    /// Search the terms and definitions of all lists
+    Search(SearchArgs),
+}
+
+impl Command {
+    /// Commands which only read the store can run alongside each other
+    pub fn lock_kind(&self) -> LockKind {
+        match self {
+            Self::Ls(_) | Self::Show(_) | Self::Export(_) | Self::Stats(_) => LockKind::Shared,
+            // Updating the search index writes to the store
+            Self::Search(x) if !x.index => LockKind::Shared,
+            _ => LockKind::Exclusive,
+        }
+    }
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct ShowArgs {
-    pub ids: Vec<usize>,
+    pub ids: Vec<ListHandle>,
     #[clap(short, long)]
     pub porcelain: bool,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct ExportArgs {
+    pub id: ListHandle,
+    /// The format to export to, guessed by the extension of the output file if not given
+    #[clap(value_enum, short, long)]
+    pub format: Option<ListFormat>,
+    /// The file to write to, stdout is used if not given
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+    /// Also export the direction and the amount of times each word was answered correctly
+    #[clap(short, long)]
+    pub progress: bool,
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct GCArgs {
     #[clap(short, long)]
@@ -49,16 +99,23 @@ pub struct GCArgs {
 
 #[derive(Args, Debug, Clone)]
 pub struct RmArgs {
-    pub ids: Vec<usize>,
+    pub ids: Vec<ListHandle>,
     #[clap(short, long)]
     pub force: bool,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct MvArgs {
+    #[clap(required = true)]
+    pub ids: Vec<ListHandle>,
+    pub folder: PathBuf,
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct NewArgs {
     pub name: String,
-    pub term_lang: String,
-    pub def_lang: String,
+    pub term_lang: LanguageTag,
+    pub def_lang: LanguageTag,
     #[clap(short, long)]
     pub dir: Option<PathBuf>,
     #[clap(value_enum, long)]
@@ -67,19 +124,119 @@ pub struct NewArgs {
 
 #[derive(Args, Debug, Clone)]
 pub struct TryArgs {
-    pub id: usize,
+    /// The lists to practice, all of them are merged into one session
+    #[clap(required = true)]
+    pub ids: Vec<ListHandle>,
     #[clap(value_enum)]
     pub method: TryMethod,
+    /// Defaults to the direction of the session which is resumed
     #[clap(value_enum, short, long)]
     pub direction: Option<WordsDirection>,
+    /// Shuffle the words when starting a new session, a resumed session keeps its order
     #[clap(short, long)]
     pub shuffle: bool,
+    /// Start over instead of resuming where you left off
+    #[clap(short, long, alias = "reset")]
+    pub restart: bool,
+    /// Shuffle the words of a new session with this seed, so they are asked in the same order
+    /// every time. The multiple choice options are the same every time as well.
+    #[clap(long)]
+    pub seed: Option<u64>,
+    /// The ID's are folders, practice all lists in them and their subfolders
+    #[clap(short = 'F', long)]
+    pub folder: bool,
+    /// The ID's are languages, practice all lists which have one of them as the language of the
+    /// terms or the definitions
+    #[clap(short = 'L', long, conflicts_with = "folder")]
+    pub lang: bool,
+    /// Which words to practice. Where you left off is only saved when practicing all words.
+    #[clap(value_enum, short = 'M', long)]
+    pub mode: Option<PracticeMode>,
+    /// Practice at most this many of the words chosen by `--mode`
+    #[clap(long, requires = "mode")]
+    pub size: Option<usize>,
+    /// End the session after this many minutes, keeping the progress
+    #[clap(short, long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub minutes: Option<u64>,
+    /// End the session after this many answers, keeping the progress
+    #[clap(short, long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub count: Option<usize>,
+    #[clap(flatten)]
+    pub judgement: JudgementArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ConfigureArgs {
+    /// The list to configure, the global config is changed if this is not given
+    pub id: Option<ListHandle>,
+    /// Remove the list's own config, so it uses the global config again
+    #[clap(long, requires = "id")]
+    pub unset: bool,
+    /// Your native language (code or name), lists which practice a language together with it are
+    /// shown first when filtering by language. Pass an empty string to unset it.
+    #[clap(long, conflicts_with = "id")]
+    pub native_language: Option<String>,
+    #[clap(flatten)]
+    pub judgement: JudgementArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ReviewArgs {
+    #[clap(value_enum, default_value = "write")]
+    pub method: TryMethod,
+    /// How many words which have never been reviewed to add to the session
+    #[clap(short, long, default_value_t = 10)]
+    pub new: usize,
+    /// The maximum amount of words to review
     #[clap(short, long)]
-    pub reset: bool,
+    pub limit: Option<usize>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct StatsArgs {
+    pub id: Option<ListHandle>,
+    /// How many of the hardest words and the most recent sessions to show
+    #[clap(short, long, default_value_t = 10)]
+    pub limit: usize,
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct ListArgs {
-    /// Optional filter by language
-    pub filter: Option<String>,
+    /// Only show lists with this language (code or name) on either side, ranked by your native
+    /// language
+    pub filter: Option<LanguageTag>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SearchArgs {
+    pub query: String,
+    /// Only search the terms
+    #[clap(short, long, conflicts_with = "definitions")]
+    pub terms: bool,
+    /// Only search the definitions
+    #[clap(short, long)]
+    pub definitions: bool,
+    /// The query is a regular expression
+    #[clap(short, long)]
+    pub regex: bool,
+    /// Ignore case and diacritics
+    #[clap(short, long)]
+    pub normalised: bool,
+    /// Use an index of the words in all lists, which is a lot faster when there are many lists.
+    /// The index is created the first time and kept up to date every time it is used.
+    #[clap(short, long)]
+    pub index: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_language_conflicts_with_id() {
+        let error = Cli::try_parse_from(["rwds-cli", "configure", "1", "--native-language", "nl"])
+            .unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        assert!(Cli::try_parse_from(["rwds-cli", "configure", "--native-language", "nl"]).is_ok());
+    }
 }
@@ -1,13 +1,27 @@
-use std::{fs::File, io::Write, process::Command};
+use std::{fs::File, io::Write, path::Path, process::Command, time::Duration};
 
+use chrono::Local;
 use clap::Parser;
-use color_eyre::{Help, Result, eyre::eyre};
-use itertools::Itertools;
+use color_eyre::{
+    Help, Result,
+    eyre::{Context, eyre},
+};
 
-use args::{GCArgs, ListArgs, NewArgs, RmArgs, ShowArgs, TryArgs};
-use ron::ser::PrettyConfig;
-use rusty_words_common::model::{ImportArgs, WordsDirection, WordsIndex, WordsList};
-use rusty_words_common::paths::{index_file, root_dir, words_file_exists};
+use args::{
+    ConfigureArgs, ExportArgs, GCArgs, ListArgs, MvArgs, NewArgs, ReviewArgs, RmArgs, SearchArgs,
+    ShowArgs, StatsArgs, TryArgs,
+};
+use rusty_words_common::config::Config;
+use rusty_words_common::folders::{self, FolderTree};
+use rusty_words_common::formats::{self, ListFormat};
+use rusty_words_common::history::{Accuracy, Stats};
+use rusty_words_common::model::{
+    DuplicateLine, ImportArgs, Language, PrimitiveWordsList, WordsIndex, WordsList,
+};
+use rusty_words_common::paths::{root_dir, words_file_exists};
+use rusty_words_common::search::{Query, SearchField, SearchIndex};
+use rusty_words_common::store::{self, StoreLock};
+use tui::TryOptions;
 
 mod args;
 mod tui;
@@ -27,28 +41,16 @@ fn main() -> Result<()> {
     //
     // We always save our changes, unless we do an early return, implying there is no need to save
     // the store.
-    let index_filename = index_file()?;
-    let mut index_file = File::options()
-        .read(true)
-        .write(true)
-        .create(true)
-        .truncate(false) // I think this is correct?
-        .open(&index_filename)?;
-
-    let mut index = match ron::de::from_reader(&mut index_file) {
-        Ok(index) => index,
-        _ if index_file.metadata()?.len() == 0 => WordsIndex::default(),
-        Err(e) => {
-            return Err(eyre!(
-                "There is an error in the index file which cannot be resolved. Exiting."
-            )
-            .with_error(|| e));
-        }
-    };
+    //
+    // The lock is held until we return, so other processes can't change the index in the meantime.
+    let lock = StoreLock::acquire(args.command.lock_kind())?;
+    let mut index = WordsIndex::load()?;
+    index.warn_unparsed_languages()?;
 
     match args.command {
         args::Command::Import(args) => {
-            let data = std::fs::read_to_string(&args.filename)?;
+            let data = formats::decode(&std::fs::read(&args.filename)?, args.parse.encoding)
+                .with_context(|| format!("while trying to import {}", args.filename.display()))?;
             let name = args.filename.file_stem(); // ! previously used file_prefix but
             // it says in nightly for way too
             // long
@@ -60,7 +62,8 @@ fn main() -> Result<()> {
                 std::io::stdin().read_line(&mut name)?;
                 name
             };
-            let id = index.import_list(&name, &data, &args)?;
+            let (id, duplicate_lines) = index.import_list(&name, &data, &args)?;
+            print_duplicate_lines(&duplicate_lines);
             println!(
                 "Successfully imported words list `{}` from `{}` with ID {}.",
                 name,
@@ -70,10 +73,9 @@ fn main() -> Result<()> {
         }
         args::Command::Show(ShowArgs { ids, porcelain }) => {
             for id in ids {
-                let meta = index.get(id)?;
+                let meta = index.get(index.resolve(&id)?)?;
                 let words_file = words_file_exists(&root_dir, &meta.uuid)?;
-                let mut words_file = File::open(words_file)?;
-                let words: WordsList = ron::de::from_reader(&mut words_file)?;
+                let words: WordsList = store::read_ron(&words_file)?;
                 if porcelain {
                     println!("{meta:#}\n{words:#}");
                 } else {
@@ -82,87 +84,221 @@ fn main() -> Result<()> {
             }
             return Ok(());
         }
-        args::Command::Ls(ListArgs { filter }) => {
-            let map = index
-                .lists
-                .into_iter()
-                .enumerate()
-                .into_group_map_by(|(_, x)| x.folder.to_owned());
+        args::Command::Export(ExportArgs {
+            id,
+            format,
+            output,
+            progress,
+        }) => {
+            let id = index.resolve(&id)?;
+            let meta = index.get(id)?;
+            let words_file = words_file_exists(&root_dir, &meta.uuid)?;
+            let words: WordsList = store::read_ron(&words_file)?;
+            let format = format
+                .or_else(|| output.as_deref().map(ListFormat::from_path))
+                .unwrap_or_default();
+            let exported = formats::export(&words, format, progress)?;
+            match output {
+                Some(output) => {
+                    store::write_atomic(&output, exported.as_bytes())?;
+                    println!("Successfully exported list {id} to `{}`.", output.display());
+                }
+                None => print!("{exported}"),
+            }
+            return Ok(());
+        }
+        args::Command::Stats(StatsArgs { id, limit }) => {
+            let list = id
+                .map(|id| index.resolve(&id).and_then(|id| index.get(id)))
+                .transpose()?
+                .map(|x| x.uuid);
+            let history = tui::load_history()?;
+            let stats = Stats::new(
+                history
+                    .iter()
+                    .filter(|x| list.is_none_or(|list| x.list == list)),
+            );
+            if stats.total.total == 0 {
+                println!("Nothing has been practiced yet.");
+                return Ok(());
+            }
+            let accuracy =
+                |x: &Accuracy| format!("{}/{}\t{:.1}%", x.correct, x.total, x.percentage());
 
-            for (folder, lists) in map.into_iter().sorted_by(|(x, _), (y, _)| x.cmp(y)) {
+            println!("Total: {}\n", accuracy(&stats.total));
+            println!("Accuracy per day:");
+            for (day, x) in &stats.per_day {
+                println!("{day}\t{}", accuracy(x));
+            }
+
+            println!("\nHardest words:");
+            for (list, terms, x) in stats.hardest.iter().take(limit) {
+                let name = index
+                    .lists
+                    .iter()
+                    .find(|meta| meta.uuid == *list)
+                    .map_or("deleted list", |meta| meta.name.as_str());
                 println!(
-                    "{}:",
-                    folder
-                        .map(|x| x.to_string_lossy().to_string())
-                        .unwrap_or_else(|| String::from("No folder"))
+                    "{} ({name})\t{} wrong, {} correct",
+                    terms.join(", "),
+                    x.wrong(),
+                    x.correct
                 );
-                let mut seen_first = false;
+            }
 
-                for (id, list) in lists.into_iter().filter(|(_, list)| {
-                    filter.is_none() || filter == list.terms.0 || filter == list.definition.0
-                }) {
-                    seen_first |= true;
-                    println!("{}. {}", id + 1, list.name);
+            println!("\nMost recent sessions:");
+            for session in stats.sessions.iter().rev().take(limit) {
+                let secs = session.duration.as_secs();
+                println!(
+                    "{}\t{}m {:02}s\t{}",
+                    session.start.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                    secs / 60,
+                    secs % 60,
+                    accuracy(&session.accuracy)
+                );
+            }
+            return Ok(());
+        }
+        args::Command::Ls(ListArgs {
+            filter: Some(filter),
+        }) => {
+            let ids = index.lists_with_language(&filter);
+            if ids.is_empty() {
+                println!("No lists...");
+            }
+            for id in ids {
+                let list = &index.lists[id - 1];
+                print!(
+                    "{id}. {} ({})\t{} - {}",
+                    list.name,
+                    list.short_uuid(),
+                    list.terms,
+                    list.definition
+                );
+                match list.folder {
+                    Some(ref folder) => println!("\tin {}", folders::display(Some(folder))),
+                    None => println!(),
                 }
+            }
+            return Ok(());
+        }
+        args::Command::Search(SearchArgs {
+            query,
+            terms,
+            definitions,
+            regex,
+            normalised,
+            index: use_index,
+        }) => {
+            let field = match (terms, definitions) {
+                (true, _) => SearchField::Terms,
+                (_, true) => SearchField::Definitions,
+                _ => SearchField::Both,
+            };
+            let query = Query::new(&query, field, regex, normalised)?;
+            let search_index = match use_index {
+                true => {
+                    let mut search_index = SearchIndex::load()?;
+                    if search_index.update(&index)? {
+                        search_index.save()?;
+                    }
+                    Some(search_index)
+                }
+                false => None,
+            };
 
-                if !seen_first {
-                    println!("No lists...");
+            let candidates = search_index.as_ref().map(|x| x.candidates(&query));
+
+            let mut found = false;
+            for (i, meta) in index.lists.iter().enumerate() {
+                if candidates
+                    .as_ref()
+                    .is_some_and(|x| !x.may_match(&meta.uuid))
+                {
+                    continue;
+                }
+                let Ok(words_file) = words_file_exists(&root_dir, &meta.uuid) else {
+                    continue;
+                };
+                let words: WordsList = match store::read_ron(&words_file) {
+                    Ok(words) => words,
+                    Err(e) => {
+                        eprintln!("Warning: skipping list {} ({}): {e:#}", i + 1, meta.name);
+                        continue;
+                    }
+                };
+                let matches = query.search(&words);
+                if matches.is_empty() {
+                    continue;
                 }
+                found = true;
+                println!("{}. {}", i + 1, meta.name);
+                for entry in matches.into_iter().map(|x| &words.0[x]) {
+                    println!(
+                        "  {}\t{}",
+                        entry.terms.join(", "),
+                        entry.definitions.join(", ")
+                    );
+                }
+            }
+            if !found {
+                println!("Nothing found.");
             }
             return Ok(());
         }
-        args::Command::Rm(RmArgs { mut ids, force }) => {
-            ids.sort_unstable();
-            ids.dedup();
-            ids.reverse();
-            for id in ids {
-                let mut result = || -> Result<()> {
-                    let meta = index.get(id)?;
+        args::Command::Ls(ListArgs { filter: None }) => {
+            let tree = FolderTree::new(&index);
+            if tree.is_empty() {
+                println!("No lists...");
+            }
+            print_tree(&index, &tree, 0);
+            return Ok(());
+        }
+        args::Command::Rm(RmArgs { ids, force }) => {
+            // Resolve everything before removing anything, because removing a list changes the
+            // ID's of the lists after it
+            let mut uuids = Vec::with_capacity(ids.len());
+            for id in &ids {
+                let result = || -> Result<_> {
+                    let meta = index.get(index.resolve_exact(id)?)?;
                     let words_file = words_file_exists(&root_dir, &meta.uuid)?;
-                    index.remove(id)?;
-                    if let Err(e) = std::fs::remove_file(words_file) {
-                        eprintln!(
-                            "Error while removing list `{}` (list ID {}) is not important so it is ignored.",
-                            id, e
-                        );
-                    }
-                    Ok(())
+                    Ok((meta.uuid, words_file))
                 };
                 match result() {
+                    Ok(x) => uuids.push(x),
                     Err(e) if !force => {
                         return Err(e);
                     }
                     _ => (),
                 }
             }
+            uuids.sort_unstable();
+            uuids.dedup();
+            for (uuid, words_file) in uuids {
+                let Some(meta) = index.remove_uuid(&uuid) else {
+                    continue;
+                };
+                if let Err(e) = std::fs::remove_file(words_file) {
+                    eprintln!(
+                        "Error while removing list `{}` ({}) is not important so it is ignored: {}",
+                        meta.name, uuid, e
+                    );
+                }
+            }
         }
         args::Command::GarbageCollect(GCArgs { dry_run }) => {
-            let mut patterns = index
-                .lists
-                .iter()
-                .enumerate()
-                .map(|(i, x)| (i, x.uuid, words_file_exists(&root_dir, &x.uuid)))
-                .collect_vec();
-            patterns = patterns
-                .into_iter()
-                .filter(|(_, _, x)| x.is_ok())
-                .collect_vec();
-            let mut not_exists = patterns
-                .iter()
-                .filter(|(_, _, x)| x.is_err())
-                .map(|(i, x, _)| (i, x))
-                .collect_vec();
-            not_exists.sort_by_key(|(i, _)| *i);
-            not_exists.reverse();
-            for (i, not_exists) in not_exists {
+            let mut patterns = Vec::with_capacity(index.lists.len());
+            let mut not_exists = Vec::new();
+            for meta in &index.lists {
+                match words_file_exists(&root_dir, &meta.uuid) {
+                    Ok(path) => patterns.push(path),
+                    Err(_) => not_exists.push(meta.uuid),
+                }
+            }
+            for not_exists in not_exists {
                 if !dry_run {
-                    println!(
-                        "Removing {} from index (id: {}) (file doesn't exist)",
-                        not_exists,
-                        i + 1
-                    );
-                    // 1-indexed
-                    index.remove(i + 1)?;
+                    println!("Removing {} from index (file doesn't exist)", not_exists);
+                    index.remove_uuid(&not_exists);
                 } else {
                     println!(
                         "Would remove {} from index (file doesn't exist)",
@@ -170,10 +306,6 @@ fn main() -> Result<()> {
                     );
                 }
             }
-            let patterns = patterns
-                .into_iter()
-                .map(|(_, _, x)| x.unwrap())
-                .collect_vec();
             for file in std::fs::read_dir(root_dir)?
                 .filter_map(|entry| entry.ok().map(|ok| ok.path()))
                 .filter(|path| {
@@ -201,35 +333,9 @@ fn main() -> Result<()> {
         }) => {
             let path = root_dir.join("temp.tsv");
             drop(File::create(&path)?);
-            let editor = std::env::var_os("EDITOR");
-            let found = editor.is_some();
-            let editor = editor.unwrap_or(if cfg!(windows) {
-                "notepad".into()
-            } else if cfg!(target_os = "macos") {
-                "/Applications/TextEdit.app/Contents/MacOS/TextEdit".into()
-            } else {
-                // Let's hope you have vim in this case
-                "vim".into()
-            });
-            let returned = Command::new(&editor)
-                .arg(&path)
-                .spawn()
-                .with_note(|| "while trying to spawn your editor")
-                .with_note(|| format!("tried editor {}", editor.to_string_lossy()))
-                .with_note(|| {
-                    if found {
-                        "tried because $EDITOR was set"
-                    } else {
-                        "tried because $EDITOR wasn't set (default value is notepad/TextEdit/vim)"
-                    }
-                })
-                .with_suggestion(|| "Try setting $EDITOR correctly (or installing vim)")?
-                .wait()?;
-            if !returned.success() {
-                color_eyre::eyre::bail!("Editor exited with nonzero exitcode");
-            }
+            open_editor(&path)?;
             let data = std::fs::read_to_string(&path)?;
-            let id = index.import_list(
+            let (id, duplicate_lines) = index.import_list(
                 &name,
                 &data,
                 &ImportArgs {
@@ -238,32 +344,226 @@ fn main() -> Result<()> {
                     def_lang: Some(def_lang),
                     dir,
                     direction,
+                    format: Some(ListFormat::Tsv),
+                    parse: Default::default(),
                 },
             )?;
+            print_duplicate_lines(&duplicate_lines);
             println!("Successfully created list {id}.");
         }
         args::Command::Try(TryArgs {
-            id,
+            ids: handles,
             method,
             direction,
             shuffle,
-            reset,
+            restart,
+            seed,
+            folder,
+            lang,
+            mode,
+            size,
+            minutes,
+            count,
+            judgement,
         }) => {
-            tui::try_list(
-                &mut index,
-                id,
+            let config = Config::load()?;
+            let mut ids = Vec::new();
+            for handle in &handles {
+                let found = if folder {
+                    let folder = folders::normalise(Path::new(&handle.0))?;
+                    index.lists_in(folder.as_deref())?
+                } else if lang {
+                    index.lists_with_language(&handle.0.parse()?)
+                } else {
+                    vec![index.resolve(handle)?]
+                };
+                if found.is_empty() {
+                    return Err(eyre!("There are no lists in `{handle}`."));
+                }
+                for id in found {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+            }
+            let mut judgement_config = match ids[..] {
+                [id] => index.get(id)?.judgement.clone(),
+                _ => None,
+            }
+            .unwrap_or(config.judgement);
+            judgement.apply_to(&mut judgement_config);
+            let options = TryOptions {
                 method,
-                direction.unwrap_or(WordsDirection::Auto),
+                direction,
                 shuffle,
-                reset,
-            )?;
+                restart,
+                seed,
+                mode: mode.unwrap_or_default(),
+                size,
+                duration: minutes.map(|x| Duration::from_secs(x * 60)),
+                count,
+            };
+            // Saves the index itself, as it is not locked while practicing
+            return tui::try_list(&index, lock, &ids, &options, &judgement_config);
+        }
+        args::Command::Review(ReviewArgs { method, new, limit }) => {
+            let config = Config::load()?;
+            return tui::review(&index, lock, method, new, limit, &config.judgement);
+        }
+        args::Command::Configure(ConfigureArgs {
+            id,
+            unset,
+            native_language,
+            judgement,
+        }) => {
+            let mut config = Config::load()?;
+            let configures_language = native_language.is_some();
+            if let Some(language) = native_language {
+                index.native_language = match language.trim() {
+                    "" => Language::Unset,
+                    language => Language::Tag(language.parse()?),
+                };
+                println!("Your native language is {}.", index.native_language);
+            }
+            match id {
+                Some(id) => {
+                    let id = index.resolve(&id)?;
+                    let meta = index.get_mut(id)?;
+                    if unset {
+                        meta.judgement = None;
+                        println!("List {id} now uses the global config: {}", config.judgement);
+                    } else {
+                        let list_config = meta.judgement.get_or_insert(config.judgement);
+                        judgement.apply_to(list_config);
+                        println!("Configured list {id}: {list_config}");
+                    }
+                }
+                // Only the native language is configured, the global config stays as it is
+                None if configures_language && judgement.is_empty() => (),
+                None => {
+                    judgement.apply_to(&mut config.judgement);
+                    config.save()?;
+                    println!("Configured globally: {}", config.judgement);
+                }
+            }
+        }
+        args::Command::Mv(MvArgs { ids, folder }) => {
+            let folder = folders::normalise(&folder)?;
+            for id in ids {
+                let id = index.resolve_exact(&id)?;
+                index.get_mut(id)?.folder = folder.clone();
+            }
+            println!("Moved to {}.", folders::display(folder.as_deref()));
+        }
+        args::Command::Mkdir { folder } => {
+            let Some(folder) = folders::normalise(&folder)? else {
+                return Err(eyre!("The root folder always exists."));
+            };
+            if !index.create_folder(&folder) {
+                return Err(eyre!("Folder `{}` already exists.", folder.display()));
+            }
+            println!("Created folder {}.", folders::display(Some(&folder)));
         }
-        _ => todo!(),
+        args::Command::RenameFolder { from, to } => {
+            let Some(from) = folders::normalise(&from)? else {
+                return Err(eyre!("Can't rename the root folder."));
+            };
+            let to = folders::normalise(&to)?;
+            let moved = index.rename_folder(&from, to.as_deref())?;
+            println!(
+                "Moved folder {} and the {moved} lists in it to {}.",
+                folders::display(Some(&from)),
+                folders::display(to.as_deref())
+            );
+        }
+        args::Command::Rmdir { folder } => {
+            let Some(folder) = folders::normalise(&folder)? else {
+                return Err(eyre!("Can't remove the root folder."));
+            };
+            let moved = index.remove_folder(&folder)?;
+            println!(
+                "Removed folder {}, {moved} lists were moved out of it.",
+                folders::display(Some(&folder))
+            );
+        }
+        args::Command::Edit { id } => {
+            let id = index.resolve(&id)?;
+            let meta = index.get(id)?;
+            let words_file = words_file_exists(&root_dir, &meta.uuid)?;
+            let words: WordsList = store::read_ron(&words_file)?;
+
+            let path = root_dir.join("temp.tsv");
+            write!(&mut File::create(&path)?, "{}", words.to_tsv())?;
+            open_editor(&path)?;
+            let data = std::fs::read_to_string(&path)?;
+            let edited = PrimitiveWordsList::try_from(data.as_str())
+                .with_note(|| format!("while trying to edit list {id}"))?;
+            print_duplicate_lines(edited.duplicate_lines());
+
+            let merged = words.clone().merge(WordsList::from(edited));
+            if merged == words {
+                println!("List {id} was not changed.");
+                return Ok(());
+            }
+            store::write_ron(&words_file, &merged)?;
+
+            let meta = index.get_mut(id)?;
+            // The session is kept, it finds its entries again by their terms
+            meta.last_modified = chrono::Utc::now();
+            println!("Successfully edited list {id}.");
+        }
+    }
+
+    index.save()?;
+
+    Ok(())
+}
+
+fn print_duplicate_lines(duplicate_lines: &[DuplicateLine]) {
+    for x in duplicate_lines {
+        eprintln!("Warning: {x}.");
     }
+}
 
-    let ser = ron::ser::to_string_pretty(&index, PrettyConfig::default())?;
-    let mut index_file = File::create(index_filename)?;
-    write!(&mut index_file, "{ser}")?;
+fn print_tree(index: &WordsIndex, tree: &FolderTree, depth: usize) {
+    let indent = "  ".repeat(depth);
+    for &id in &tree.lists {
+        let list = &index.lists[id - 1];
+        println!("{indent}{id}. {} ({})", list.name, list.short_uuid());
+    }
+    for (name, subfolder) in &tree.subfolders {
+        println!("{indent}{name}/");
+        print_tree(index, subfolder, depth + 1);
+    }
+}
 
+fn open_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var_os("EDITOR");
+    let found = editor.is_some();
+    let editor = editor.unwrap_or(if cfg!(windows) {
+        "notepad".into()
+    } else if cfg!(target_os = "macos") {
+        "/Applications/TextEdit.app/Contents/MacOS/TextEdit".into()
+    } else {
+        // Let's hope you have vim in this case
+        "vim".into()
+    });
+    let returned = Command::new(&editor)
+        .arg(path)
+        .spawn()
+        .with_note(|| "while trying to spawn your editor")
+        .with_note(|| format!("tried editor {}", editor.to_string_lossy()))
+        .with_note(|| {
+            if found {
+                "tried because $EDITOR was set"
+            } else {
+                "tried because $EDITOR wasn't set (default value is notepad/TextEdit/vim)"
+            }
+        })
+        .with_suggestion(|| "Try setting $EDITOR correctly (or installing vim)")?
+        .wait()?;
+    if !returned.success() {
+        color_eyre::eyre::bail!("Editor exited with nonzero exitcode");
+    }
     Ok(())
 }
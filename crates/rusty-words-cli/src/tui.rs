@@ -3,10 +3,13 @@ use num::integer::div_floor;
 use std::{
     borrow::Cow,
     collections::{HashMap, VecDeque},
-    fs::File,
-    io::Write,
+    io::{Stdout, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
 };
 
+use chrono::Utc;
+
 use color_eyre::{Result, eyre::eyre};
 use crossterm::{
     event::{self, Event, KeyCode, KeyModifiers},
@@ -14,45 +17,277 @@ use crossterm::{
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use itertools::Itertools;
-use rand::prelude::SliceRandom;
+use rand::{Rng, prelude::SliceRandom};
 use ratatui::{
     Frame, Terminal,
     backend::{Backend, CrosstermBackend},
     layout::{Alignment, Constraint, Direction, Flex, Layout},
     style::{Color, Modifier, Style},
-    text::{Line, Span, Text, ToLine, ToSpan},
+    text::{Line, Span, Text, ToSpan},
     widgets::{Block, Borders, Paragraph, Wrap},
 };
-use ron::ser::PrettyConfig;
 use rusty_words_common::{
-    judgement::{TryMethod, check_word},
-    model::{WordsDirection, WordsIndex, WordsList, WordsMeta},
-    paths::{index_file, root_dir, words_file_exists},
+    history::{self, Accuracy, HistoryEntry, HistoryLog},
+    judgement::{
+        self, BothSplit, Judgement, JudgementConfig, TryMethod, check_word, check_word_with,
+    },
+    model::{Language, WordsDirection, WordsEntry, WordsIndex, WordsList, WordsMeta},
+    paths::{root_dir, words_file_exists},
+    practice::{self, PracticeMode, Resumed, SessionState},
+    scheduler::{self, ReviewState},
+    store::{self, LockKind, StoreLock},
 };
+use similar::{ChangeTag, TextDiff};
 use tui_input::Input;
 use tui_input::backend::crossterm as input_backend;
+use uuid::Uuid;
+
+/// The entries of one or more lists which are practiced together
+pub struct Session<'a> {
+    pub list: WordsList<'a>,
+    /// For every entry, the list it came from and its position in that list
+    pub origins: Vec<(Uuid, usize)>,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(lists: &[(Uuid, WordsList<'a>)]) -> Self {
+        let mut list = Vec::new();
+        let mut origins = Vec::new();
+        for (uuid, words) in lists {
+            list.extend(words.0.iter().cloned());
+            origins.extend((0..words.0.len()).map(|i| (*uuid, i)));
+        }
+        Self {
+            list: WordsList(list),
+            origins,
+        }
+    }
 
+    /// Only keeps the entries to practice in `mode`, at most `size` of them
+    pub fn select(&mut self, mode: PracticeMode, size: Option<usize>) {
+        let lists = self.origins.iter().map(|(uuid, _)| *uuid).collect_vec();
+        let wrong_last_session = practice::wrong_last_session(&self.list.0, &lists);
+        let selected = practice::select(&self.list.0, mode, &wrong_last_session)
+            .into_iter()
+            .take(size.unwrap_or(usize::MAX))
+            .collect_vec();
+        self.list = WordsList(selected.iter().map(|&i| self.list.0[i].clone()).collect());
+        self.origins = selected.iter().map(|&i| self.origins[i]).collect();
+    }
+
+    /// Writes the entries back to the lists they came from
+    pub fn write_back(self, lists: &mut [(Uuid, WordsList<'a>)]) {
+        for (entry, (uuid, i)) in self.list.0.into_iter().zip(self.origins) {
+            if let Some((_, words)) = lists.iter_mut().find(|(x, _)| *x == uuid) {
+                words.0[i] = entry;
+            }
+        }
+    }
+}
+
+/// How the words are practiced by `try`
+pub struct TryOptions {
+    pub method: TryMethod,
+    /// The direction of the session which is resumed is used if this is not given
+    pub direction: Option<WordsDirection>,
+    /// Only used when starting a new session
+    pub shuffle: bool,
+    /// Start a new session instead of resuming the last one
+    pub restart: bool,
+    /// Makes the shuffle and the multiple choice options the same every time
+    pub seed: Option<u64>,
+    pub mode: PracticeMode,
+    /// Practice at most this many of the entries chosen by `mode`
+    pub size: Option<usize>,
+    /// End the session after this long, even if not every word has been learned
+    pub duration: Option<Duration>,
+    /// End the session after this many answers
+    pub count: Option<usize>,
+}
+
+/// Practices the lists with these ID's in one session. The progress is only saved if all entries of
+/// a single list are practiced.
+///
+/// The store is only locked while loading and saving, so it can be used while practicing. `lock` is
+/// released when the session starts.
 pub fn try_list(
-    index: &mut WordsIndex,
-    id: usize,
+    index: &WordsIndex,
+    lock: StoreLock,
+    ids: &[usize],
+    options: &TryOptions,
+    config: &JudgementConfig,
+) -> Result<()> {
+    let root_dir = root_dir()?;
+    let mut lists = Vec::with_capacity(ids.len());
+    let mut files = Vec::with_capacity(ids.len());
+    for &id in ids {
+        let meta = index.get(id)?;
+        let words_file = words_file_exists(&root_dir, &meta.uuid)?;
+        lists.push((meta.uuid, store::read_ron::<WordsList>(&words_file)?));
+        files.push((modified(&words_file), words_file));
+    }
+    let mut session = Session::new(&lists);
+    let selecting = options.mode != PracticeMode::All || options.size.is_some();
+    if selecting {
+        session.select(options.mode, options.size);
+        if session.list.0.is_empty() {
+            return Err(match options.mode {
+                PracticeMode::All => eyre!("There are no words to practice."),
+                PracticeMode::Mistakes => {
+                    eyre!("No words were answered wrong in the last session.")
+                }
+                PracticeMode::Weakest => eyre!("None of the words have been practiced yet."),
+                PracticeMode::New => eyre!("Every word has been practiced already."),
+            });
+        }
+    }
+
+    let mut meta = match ids {
+        [id] if !selecting => index.get(*id)?.clone(),
+        _ => merged_meta(index, ids),
+    };
+
+    drop(lock);
+    let res =
+        with_terminal(|terminal| try_tui(&mut session, terminal, &mut meta, options, config))?;
+
+    let _lock = StoreLock::acquire(LockKind::Exclusive)?;
+    let mut index = WordsIndex::load()?;
+    session.write_back(&mut lists);
+    let mut saved = true;
+    for ((loaded, words_file), (_, words)) in files.iter().zip(&lists) {
+        match modified(words_file) == *loaded {
+            true => store::write_ron(words_file, words)?,
+            false => saved = false,
+        }
+    }
+    // Where the session was left off only makes sense for the list as it was practiced
+    match index.lists.iter_mut().find(|x| x.uuid == meta.uuid) {
+        Some(stored) if saved => stored.session = meta.session,
+        _ => (),
+    }
+    index.save()?;
+    if !saved {
+        eprintln!("Warning: {CHANGED_WHILE_PRACTICING}");
+    }
+
+    res
+}
+
+const CHANGED_WHILE_PRACTICING: &str =
+    "a list was changed or removed while practicing, the answers given on it were not saved.";
+
+/// When `path` was last modified, `None` if it doesn't exist (anymore)
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|x| x.modified()).ok()
+}
+
+/// Loads the history, warning about the lines which couldn't be read
+pub fn load_history() -> Result<Vec<HistoryEntry>> {
+    let (history, skipped) = history::load()?;
+    if skipped > 0 {
+        eprintln!("Warning: skipped {skipped} unreadable line(s) in the history.");
+    }
+    Ok(history)
+}
+
+/// The metadata shown while practicing several lists at once
+fn merged_meta(index: &WordsIndex, ids: &[usize]) -> WordsMeta {
+    let metas = ids.iter().map(|&id| &index.lists[id - 1]).collect_vec();
+    let language = |f: fn(&WordsMeta) -> &Language| {
+        metas
+            .iter()
+            .map(|&x| f(x).tag())
+            .all_equal_value()
+            .ok()
+            .flatten()
+    };
+    WordsMeta::new(
+        metas.iter().map(|x| x.name.as_str()).join(", "),
+        language(|x| &x.terms),
+        language(|x| &x.definition),
+        None,
+    )
+}
+
+/// Reviews the entries of all lists which are due according to the spaced repetition scheduler,
+/// plus at most `new` entries which have never been reviewed. Like [try_list], `lock` is released
+/// while reviewing.
+pub fn review(
+    index: &WordsIndex,
+    lock: StoreLock,
     method: TryMethod,
-    direction: WordsDirection,
-    shuffle: bool,
-    reset: bool,
+    new: usize,
+    limit: Option<usize>,
+    config: &JudgementConfig,
 ) -> Result<()> {
-    let meta = index
-        .lists
-        .get_mut(id.checked_sub(1).ok_or_else(|| {
-            eyre!("Integer underflow when trying list {id}, lists are 1-indexed.")
-        })?)
-        .ok_or_else(|| eyre!("Could not find list by ID {id}"))?;
-    if reset {
-        meta.progress = None;
-    }
-    let words_file = words_file_exists(&root_dir()?, &meta.uuid)?;
-    let mut file = File::open(&words_file)?;
-    let mut words: WordsList = ron::de::from_reader(&mut file)?;
+    let root_dir = root_dir()?;
+    let now = Utc::now();
+    let mut lists = Vec::with_capacity(index.lists.len());
+    let mut due = Vec::new();
+    let mut new_cards = Vec::new();
+    let mut loaded_at = Vec::new();
+    for (id, meta) in index.lists.iter().enumerate() {
+        // A single broken list shouldn't keep the others from being reviewed
+        let words = words_file_exists(&root_dir, &meta.uuid)
+            .and_then(|file| Ok((store::read_ron::<WordsList>(&file)?, file)));
+        let (words, words_file) = match words {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("Warning: skipping list {} ({}): {e:#}", id + 1, meta.name);
+                continue;
+            }
+        };
+        let i = lists.len();
+        let loaded = modified(&words_file);
+        for entry in scheduler::due_entries(&words, now) {
+            let due_at = words.0[entry].review.as_ref().map(|x| x.due);
+            due.push((due_at, i, entry));
+        }
+        new_cards.extend(
+            scheduler::new_entries(&words)
+                .into_iter()
+                .map(|entry| (i, entry)),
+        );
+        lists.push((id, words_file, words));
+        loaded_at.push(loaded);
+    }
+    due.sort_by_key(|(due_at, _, _)| *due_at);
+    let cards = due
+        .into_iter()
+        .map(|(_, i, entry)| (i, entry))
+        .chain(new_cards.into_iter().take(new))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect_vec();
+    if cards.is_empty() {
+        println!("Nothing to review right now.");
+        return Ok(());
+    }
+
+    drop(lock);
+    let res =
+        with_terminal(|terminal| review_tui(&mut lists, &cards, terminal, index, &method, config))?;
+
+    let _lock = StoreLock::acquire(LockKind::Exclusive)?;
+    // Only the lists with an entry in the session can have changed
+    let mut saved = true;
+    for (i, (_, words_file, words)) in lists.iter().enumerate() {
+        if cards.iter().any(|(x, _)| *x == i) {
+            match modified(words_file) == loaded_at[i] {
+                true => store::write_ron(words_file, words)?,
+                false => saved = false,
+            }
+        }
+    }
+    if !saved {
+        eprintln!("Warning: {CHANGED_WHILE_PRACTICING}");
+    }
 
+    res
+}
+
+/// Runs `f` inside of the alternate screen, restoring the terminal afterwards
+fn with_terminal<T>(f: impl FnOnce(&mut Terminal<CrosstermBackend<Stdout>>) -> T) -> Result<T> {
     unsafe {
         libc::signal(libc::SIGINT, libc::SIG_IGN);
     }
@@ -62,79 +297,518 @@ pub fn try_list(
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let res = try_tui(&mut words, &mut terminal, meta, &method, direction, shuffle);
+    let res = f(&mut terminal);
 
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    Ok(res)
+}
+
+/// Why a session ended, if the user didn't quit
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Ending {
+    /// Every word has been learned
+    Finished,
+    /// The amount of answers given `--count`
+    Count,
+    /// The time given by `--minutes` is up
+    Time,
+}
+
+impl Ending {
+    /// Why the session ends before asking the next word, if it does
+    fn check(
+        learned: usize,
+        total_words: usize,
+        answered: usize,
+        count: Option<usize>,
+        time_up: bool,
+    ) -> Option<Self> {
+        if learned >= total_words {
+            Some(Self::Finished)
+        } else if count.is_some_and(|x| answered >= x) {
+            Some(Self::Count)
+        } else if time_up {
+            Some(Self::Time)
+        } else {
+            None
+        }
+    }
+}
 
-    let ser = ron::ser::to_string_pretty(&words, PrettyConfig::default())?;
-    write!(&mut File::create(words_file)?, "{ser}")?;
+/// What happened during a session, shown when it ends
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    answers: Accuracy,
+    /// The entries which were learned during the session
+    mastered: Vec<usize>,
+    /// For every entry, how many times it was answered wrong during the session
+    mistakes: HashMap<usize, usize>,
+    hints: usize,
+}
 
-    let ser = ron::ser::to_string_pretty(&index, PrettyConfig::default())?;
-    let mut index_file = File::create(index_file()?)?;
-    write!(&mut index_file, "{ser}")?;
+impl Summary {
+    fn add(&mut self, index: usize, is_correct: bool, hints: usize) {
+        self.answers.add(is_correct);
+        self.hints += hints;
+        if !is_correct {
+            *self.mistakes.entry(index).or_default() += 1;
+        }
+    }
 
-    res
+    /// The entries which were answered wrong and not learned during the session, with their amount
+    /// of mistakes, the most first
+    fn weak(&self) -> Vec<(usize, usize)> {
+        let mut weak = self
+            .mistakes
+            .iter()
+            .filter(|(x, _)| !self.mastered.contains(x))
+            .map(|(&x, &mistakes)| (x, mistakes))
+            .collect_vec();
+        weak.sort_by_key(|&(x, mistakes)| (std::cmp::Reverse(mistakes), x));
+        weak
+    }
+}
+
+/// The words in rotation, with their position in the list and how many times they were answered
+/// correctly
+type Rotation<'a> = VecDeque<(usize, WordsEntry<'a>, usize)>;
+
+/// How a session was before an answer, so the user can undo or override it
+struct Snapshot<'a> {
+    rotation: Rotation<'a>,
+    n: usize,
+    summary: Summary,
+    index: usize,
+    /// The entry before it was answered
+    entry: WordsEntry<'a>,
+    guess: String,
+    response: Duration,
+    hints: usize,
+}
+
+impl<'a> Snapshot<'a> {
+    /// Takes a snapshot before the word at the front of the rotation is asked, the guess,
+    /// response time and hints are filled in once it has been answered
+    fn take(rotation: &Rotation<'a>, n: usize, summary: &Summary, list: &WordsList<'a>) -> Self {
+        let index = rotation.front().expect("The rotation is never empty").0;
+        Self {
+            rotation: rotation.clone(),
+            n,
+            summary: summary.clone(),
+            index,
+            entry: list.0[index].clone(),
+            guess: String::new(),
+            response: Duration::ZERO,
+            hints: 0,
+        }
+    }
+
+    /// Goes back to before the answer
+    fn restore(
+        &self,
+        rotation: &mut Rotation<'a>,
+        n: &mut usize,
+        summary: &mut Summary,
+        list: &mut WordsList<'a>,
+    ) {
+        *rotation = self.rotation.clone();
+        *n = self.n;
+        *summary = self.summary.clone();
+        list.0[self.index] = self.entry.clone();
+    }
+}
+
+/// Adds an overridden guess to the answers of `entry`
+fn accept(entry: &mut WordsEntry, guess: &str, swapped: bool) {
+    let accepted = Cow::Owned(guess.trim().to_string());
+    match swapped {
+        true => entry.terms.push(accepted),
+        false => entry.definitions.push(accepted),
+    }
+}
+
+/// A wrong answer which should be given again as a correct one
+struct Override {
+    guess: String,
+    response: Duration,
+    /// The hints which were used for the guess
+    hints: usize,
+    /// Add the guess to the answers of the entry
+    accept: bool,
 }
 
 pub fn try_tui(
-    list: &mut WordsList,
+    session: &mut Session,
     terminal: &mut Terminal<impl Write + Backend>,
     meta: &mut WordsMeta,
-    method: &TryMethod,
-    direction: WordsDirection,
-    shuffle: bool,
+    options: &TryOptions,
+    config: &JudgementConfig,
 ) -> Result<()> {
+    let Session { list, origins } = session;
     if list.0.is_empty() {
         return Ok(());
     }
     let total_words = list.0.len();
-    let mut n = meta.progress.unwrap_or(0);
-    let mut shuffle_map = HashMap::new();
-
-    if shuffle {
-        let mut random_array = (0..total_words).collect_vec();
-        let mut rng = rand::thread_rng();
-        random_array.shuffle(&mut rng);
-        shuffle_map = meta
-            .shuffle_map
-            .insert(HashMap::from_iter((0..total_words).zip(random_array)))
-            .clone();
-    }
-
-    let mut rotation: VecDeque<_> = (0..10)
-        .filter_map(|x| {
-            let index = *shuffle_map.get(&x).unwrap_or(&x);
-            // Sorry for the clone
-            list.0.get(index).map(|x| (index, x.clone(), 0))
-        })
+    let deadline = options.duration.map(|x| Instant::now() + x);
+
+    let mut state = match &meta.session {
+        Some(state) if !options.restart => state.clone(),
+        _ => SessionState::new(
+            options.seed.or_else(|| options.shuffle.then(rand::random)),
+            options.direction.unwrap_or(WordsDirection::Auto),
+            options.method,
+        ),
+    };
+    if let Some(direction) = options.direction {
+        state.direction = direction;
+    }
+    state.method = options.method;
+    let Resumed {
+        rotation: resumed,
+        learned,
+        upcoming,
+    } = state.resume(&list.0, config.rotation);
+    let mut n = learned.len();
+    let mut rng = practice::rng(options.seed);
+    // Sorry for the clone
+    let mut rotation: Rotation = resumed
+        .into_iter()
+        .map(|(index, progress)| (index, list.0[index].clone(), progress))
         .collect();
 
-    // TODO: Make this configurable
-    let total_progress: usize = 3;
+    let total_progress = config.correct_needed.max(1);
     let td_progress = div_floor(total_progress, 2);
+    let reversed = |progress: usize| match config.both_split {
+        BothSplit::Half => progress > td_progress,
+        BothSplit::Alternate => progress % 2 == 1,
+    };
     let tui_total = total_words.to_string();
 
     let term_lang = meta.terms.to_string();
     let def_lang = meta.definition.to_string();
 
+    let mut history = HistoryLog::open()?;
     let mut message = Vec::new();
-    while n < total_words {
-        let (index, front, mut progress) = rotation.pop_front().unwrap();
-        let mut ask = front.terms.as_slice();
-        let mut ans = front.definitions.as_slice();
-        let direction = direction & front.direction;
-        match direction {
-            WordsDirection::DT => std::mem::swap(&mut ask, &mut ans),
-            WordsDirection::Both if progress > td_progress => std::mem::swap(&mut ask, &mut ans),
-            _ => (),
-        };
+    let mut summary = Summary::default();
+    // Every answer of the session can be undone
+    let mut undo: Vec<Snapshot> = Vec::new();
+    // Whether the last answer was a wrong written answer, which the user can override
+    let mut overridable = false;
+    // A wrong answer the user overrode, it is given again instead of asking for the word
+    let mut overridden: Option<Override> = None;
+    let ending = loop {
+        // A wrong answer which was overridden is always given again
+        let time_up = overridden.is_none() && deadline.is_some_and(|x| Instant::now() >= x);
+        if let Some(ending) = Ending::check(
+            n,
+            total_words,
+            summary.answers.total,
+            options.count,
+            time_up,
+        ) {
+            break ending;
+        }
+        // Taken before anything changes, so accepting an overridden guess can be undone as well
+        let before = Snapshot::take(&rotation, n, &summary, list);
+        let (index, mut front, mut progress) = rotation.pop_front().unwrap();
+        let direction = state.direction & front.direction;
         let tui_direc = match direction {
             WordsDirection::Auto => WordsDirection::TD,
-            WordsDirection::Both if progress > td_progress => WordsDirection::DT,
+            WordsDirection::Both if reversed(progress) => WordsDirection::DT,
             WordsDirection::Both => WordsDirection::TD,
             e => e,
         };
+        let swapped = tui_direc == WordsDirection::DT;
+        if let Some(Override {
+            guess,
+            accept: true,
+            ..
+        }) = &overridden
+        {
+            accept(&mut front, guess, swapped);
+            accept(&mut list.0[index], guess, swapped);
+        }
+        let (ask, ans) = match swapped {
+            true => (front.definitions.as_slice(), front.terms.as_slice()),
+            false => (front.terms.as_slice(), front.definitions.as_slice()),
+        };
+        let started = Instant::now();
+        let answer = match &overridden {
+            Some(x) => Answer::Given(Judgement::Exact, x.guess.clone(), x.hints),
+            None => {
+                let app = App {
+                    message: &std::mem::take(&mut message).into(),
+                    meta,
+                    n: &n.to_string(),
+                    total_words: &tui_total,
+                    direction: &tui_direc.to_string(),
+                    ask,
+                    ans,
+                    term_lang: &term_lang,
+                    def_lang: &def_lang,
+                    deadline,
+                    overridable,
+                    undoable: !undo.is_empty(),
+                };
+                let choices = |correct| mpc_options(list, index, swapped, correct, &mut rng);
+                ask_and_check(terminal, app, &options.method, config, choices)?
+            }
+        };
+        let revealed = matches!(answer, Answer::Revealed);
+        let (judgement, guess, hints) = match answer {
+            Answer::Given(judgement, guess, hints) => (judgement, guess, hints),
+            // Giving up counts as a wrong answer
+            Answer::Revealed => (Judgement::Wrong, String::new(), 0),
+            Answer::Skipped => {
+                message = skipped(ask);
+                overridable = false;
+                rotation.push_back((index, front, progress));
+                continue;
+            }
+            // Go back to before the last answer, the word which was being asked is asked again
+            // later as it was part of the rotation then too
+            undone @ (Answer::Undo | Answer::Override(_)) => {
+                let Some(snapshot) = undo.pop() else {
+                    rotation.push_front((index, front, progress));
+                    continue;
+                };
+                // The history is only used for statistics, so failing to update it shouldn't end
+                // the session
+                let history_error = history.undo().err();
+                snapshot.restore(&mut rotation, &mut n, &mut summary, list);
+                message = match undone {
+                    // An overridden answer is given again as a correct one
+                    Answer::Override(accept) => {
+                        overridden = Some(Override {
+                            guess: snapshot.guess,
+                            response: snapshot.response,
+                            hints: snapshot.hints,
+                            accept,
+                        });
+                        Vec::new()
+                    }
+                    _ => undid(&snapshot.entry.terms),
+                };
+                message.extend(history_error.map(history_warning));
+                overridable = false;
+                continue;
+            }
+            Answer::Quit => {
+                rotation.push_front((index, front, progress));
+                meta.session = Some(left_off(&state, list, &learned, &summary, &rotation));
+                return Err(eyre!("User quit"));
+            }
+            // The word which was being asked is asked again next time
+            Answer::OutOfTime => {
+                rotation.push_front((index, front, progress));
+                break Ending::Time;
+            }
+        };
+        let is_correct = judgement.is_correct();
+        let replayed = overridden.take();
+        let response = replayed
+            .as_ref()
+            .map_or_else(|| started.elapsed(), |x| x.response);
+        let recorded = record(
+            &mut history,
+            origins[index].0,
+            &front.terms,
+            tui_direc,
+            &guess,
+            is_correct,
+            response,
+        );
+        overridable = !is_correct && !revealed && matches!(options.method, TryMethod::Write);
+        undo.push(Snapshot {
+            guess: guess.clone(),
+            response,
+            hints,
+            ..before
+        });
+        message = match (&replayed, revealed) {
+            (Some(x), _) => feedback_overridden(ask, ans, x.accept),
+            (None, true) => feedback_revealed(ask, ans),
+            (None, false) => feedback(&judgement, ask, ans, guess),
+        };
+        message.extend(recorded.err().map(history_warning));
+        summary.add(index, is_correct, hints);
+        let is_known = judgement.is_known(hints);
+        list.0[index].answered(is_known, Utc::now(), history.session());
+        if is_known {
+            progress += 1;
+            if progress == total_progress {
+                n += 1;
+                summary.mastered.push(index);
+                // Every word which is learned makes room for the next one
+                if let Some(&index) = upcoming.get(summary.mastered.len() - 1) {
+                    rotation.push_back((index, list.0[index].clone(), 0));
+                }
+                continue;
+            }
+        } else if !is_correct && config.reset_on_mistake {
+            progress = 0;
+        }
+        rotation.push_back((index, front, progress));
+    };
+
+    // Stopping early keeps the progress, just like quitting
+    meta.session = match ending {
+        Ending::Finished => None,
+        _ => Some(left_off(&state, list, &learned, &summary, &rotation)),
+    };
+    show_summary(terminal, meta, list, &summary, ending, (n, total_words))
+}
+
+/// Where the session was left off, to resume it the next time
+fn left_off(
+    state: &SessionState,
+    list: &WordsList,
+    learned: &[usize],
+    summary: &Summary,
+    rotation: &Rotation,
+) -> SessionState {
+    state.left_off(
+        &list.0,
+        rotation
+            .iter()
+            .map(|(index, _, progress)| (*index, *progress)),
+        learned.iter().chain(&summary.mastered).copied(),
+    )
+}
+
+/// Shows what happened during the session until a key is pressed
+fn show_summary<B: Backend>(
+    terminal: &mut Terminal<B>,
+    meta: &WordsMeta,
+    list: &WordsList,
+    summary: &Summary,
+    ending: Ending,
+    (n, total_words): (usize, usize),
+) -> Result<()> {
+    let bold = || Style::default().add_modifier(Modifier::BOLD);
+    let title = match ending {
+        Ending::Finished => "You learned every word!",
+        Ending::Count => "That's all the words for this session.",
+        Ending::Time => "Time's up!",
+    };
+    let weak = summary.weak();
+    let answers = &summary.answers;
+
+    let mut lines = vec![
+        Line::styled(title, bold().fg(Color::Green)),
+        Line::raw(""),
+        Line::from(vec![
+            Span::raw("Answered: "),
+            Span::styled(answers.total.to_string(), bold()),
+        ]),
+        Line::from(vec![
+            Span::raw("Accuracy: "),
+            Span::styled(
+                format!(
+                    "{}/{} ({:.1}%)",
+                    answers.correct,
+                    answers.total,
+                    answers.percentage()
+                ),
+                bold(),
+            ),
+        ]),
+        Line::from(vec![
+            Span::raw("Learned this session: "),
+            Span::styled(summary.mastered.len().to_string(), bold()),
+        ]),
+        Line::from(vec![
+            Span::raw("Hints used: "),
+            Span::styled(summary.hints.to_string(), bold()),
+        ]),
+        Line::from(vec![
+            Span::raw("Progress: "),
+            Span::styled(format!("{n} / {total_words}"), bold()),
+        ]),
+    ];
+    if !weak.is_empty() {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled("Still weak:", bold().fg(Color::Yellow)));
+        lines.extend(weak.iter().take(10).map(|&(x, mistakes)| {
+            let entry = &list.0[x];
+            Line::raw(format!(
+                "{} -> {} ({mistakes} {})",
+                entry.terms.join(", "),
+                entry.definitions.join(", "),
+                match mistakes {
+                    1 => "mistake",
+                    _ => "mistakes",
+                }
+            ))
+        }));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        "Press any key to exit",
+        Style::default().add_modifier(Modifier::DIM),
+    ));
+
+    let height = lines.len() as u16 + 2;
+    let view = Paragraph::new(Text::from(lines))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(meta.name.as_str()),
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    loop {
+        terminal.draw(|f| {
+            let area = Layout::default()
+                .flex(Flex::Center)
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(height)])
+                .split(f.area())[0];
+            f.render_widget(view.clone(), area);
+        })?;
+        if let Event::Key(_) = event::read()? {
+            return Ok(());
+        }
+    }
+}
+
+/// `lists` holds the ID's, files and entries of the lists, `cards` the positions of the entries to
+/// review in `lists` and in their list
+pub fn review_tui(
+    lists: &mut [(usize, PathBuf, WordsList)],
+    cards: &[(usize, usize)],
+    terminal: &mut Terminal<impl Write + Backend>,
+    index: &WordsIndex,
+    method: &TryMethod,
+    config: &JudgementConfig,
+) -> Result<()> {
+    let now = Utc::now();
+    let tui_total = cards.len().to_string();
+    let mut queue: VecDeque<_> = cards.iter().map(|&(i, entry)| (i, entry, true)).collect();
+    let mut n = 0;
+
+    let mut history = HistoryLog::open()?;
+    let mut message = Vec::new();
+    while let Some((i, entry, first_try)) = queue.pop_front() {
+        let (id, _, list) = &mut lists[i];
+        let meta = &index.lists[*id];
+        let front = list.0[entry].clone();
+        let swapped = match front.direction {
+            WordsDirection::DT => true,
+            WordsDirection::Both => rand::random(),
+            _ => false,
+        };
+        let (mut ask, mut ans) = (front.terms.as_slice(), front.definitions.as_slice());
+        if swapped {
+            std::mem::swap(&mut ask, &mut ans);
+        }
+        let tui_direc = match swapped {
+            false => WordsDirection::TD,
+            true => WordsDirection::DT,
+        };
+        let term_lang = meta.terms.to_string();
+        let def_lang = meta.definition.to_string();
         let app = App {
             message: &message.into(),
             meta,
@@ -145,51 +819,200 @@ pub fn try_tui(
             ans,
             term_lang: &term_lang,
             def_lang: &def_lang,
+            deadline: None,
+            overridable: false,
+            undoable: false,
         };
-        let (is_correct, guess) = match method {
-            TryMethod::Write => write_and_check(terminal, app, list, &shuffle_map),
-            TryMethod::Mpc => todo!(),
-        }?;
-        let ask = ask.join(", ");
-        let ans = ans.join(", ");
-        if is_correct {
-            message = vec![
-                Line::styled("Correct! ", Style::default().fg(Color::Green)),
-                Line::raw(format!("{} -> {}", ask, ans)),
-            ];
-
-            list.0[index].times_answered_correctly += 1;
-            progress += 1;
-            if progress == total_progress {
-                n += 1;
-                let rot = rotation.len();
-                if n <= total_words.saturating_sub(rot) {
-                    // We can add another word
-                    let next = n + rot - 1;
-                    let index = *shuffle_map.get(&next).unwrap_or(&next);
-                    rotation.push_back((index, list.0.get(index).unwrap().clone(), 0));
-                }
+        let config = meta.judgement.as_ref().unwrap_or(config);
+        let started = Instant::now();
+        let choices = |correct| mpc_options(list, entry, swapped, correct, &mut rand::thread_rng());
+        let answer = ask_and_check(terminal, app, method, config, choices)?;
+        let revealed = matches!(answer, Answer::Revealed);
+        let (judgement, guess, hints) = match answer {
+            Answer::Given(judgement, guess, hints) => (judgement, guess, hints),
+            Answer::Revealed => (Judgement::Wrong, String::new(), 0),
+            Answer::Skipped => {
+                message = skipped(ask);
+                queue.push_back((i, entry, first_try));
                 continue;
             }
+            Answer::Override(_) | Answer::Undo => {
+                unreachable!("Reviews can't be overridden or undone.")
+            }
+            // The reviews made so far are saved by the caller
+            Answer::Quit | Answer::OutOfTime => return Ok(()),
+        };
+        let is_correct = judgement.is_correct();
+        let recorded = record(
+            &mut history,
+            meta.uuid,
+            &front.terms,
+            tui_direc,
+            &guess,
+            is_correct,
+            started.elapsed(),
+        );
+        message = match revealed {
+            true => feedback_revealed(ask, ans),
+            false => feedback(&judgement, ask, ans, guess),
+        };
+        message.extend(recorded.err().map(history_warning));
+
+        // Only the first answer counts for the schedule, wrong answers are asked again at the end
+        // of the session until they are correct.
+        let entry_mut = &mut list.0[entry];
+        if first_try {
+            let quality = match (is_correct, hints) {
+                (true, 0) => scheduler::QUALITY_CORRECT,
+                (true, _) => scheduler::QUALITY_HINTED,
+                (false, _) => scheduler::QUALITY_WRONG,
+            };
+            entry_mut
+                .review
+                .get_or_insert_with(|| ReviewState::new(now))
+                .review(quality, now);
+        }
+        entry_mut.answered(judgement.is_known(hints), Utc::now(), history.session());
+        if is_correct {
+            n += 1;
         } else {
-            message = vec![
-                Line::styled("Wrong! ", Style::default().fg(Color::Red)),
-                Line::raw(format!("{} -> {}. You guessed ", ask, ans)),
-                Line::styled(
-                    guess,
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-                ),
-            ];
+            queue.push_back((i, entry, false));
         }
-        rotation.push_back((index, front, progress));
     }
     Ok(())
 }
 
+/// Appends an answer to the history, `response` is how long it took to answer
+fn record(
+    history: &mut HistoryLog,
+    list: Uuid,
+    terms: AppTerms<'_>,
+    direction: WordsDirection,
+    guess: &str,
+    correct: bool,
+    response: Duration,
+) -> Result<()> {
+    history.record(&HistoryEntry {
+        list,
+        terms: terms.iter().map(|x| x.to_string()).collect(),
+        direction,
+        guess: guess.to_string(),
+        correct,
+        response_ms: response.as_millis() as u64,
+        timestamp: Utc::now(),
+        session: history.session(),
+    })
+}
+
+fn history_warning(error: color_eyre::Report) -> Line<'static> {
+    Line::styled(
+        format!(" (Couldn't save this to the history: {error})"),
+        Style::default().fg(Color::Red),
+    )
+}
+
+fn feedback(
+    judgement: &Judgement,
+    ask: AppTerms<'_>,
+    ans: AppTerms<'_>,
+    guess: String,
+) -> Vec<Line<'static>> {
+    let ask = ask.join(", ");
+    let ans = ans.join(", ");
+    match judgement {
+        Judgement::Exact => vec![
+            Line::styled("Correct! ", Style::default().fg(Color::Green)),
+            Line::raw(format!("{} -> {}", ask, ans)),
+        ],
+        Judgement::AcceptedWithoutDiacritics => vec![
+            Line::styled("Correct! ", Style::default().fg(Color::Green)),
+            Line::raw(format!("{} -> {}", ask, ans)),
+            Line::styled(
+                format!(" Watch your accents! You typed {guess}"),
+                Style::default().fg(Color::Yellow),
+            ),
+        ],
+        Judgement::AcceptedWithTypo { expected, distance } => {
+            let mut message = vec![
+                Line::styled("Almost! ", Style::default().fg(Color::Yellow)),
+                Line::raw(format!("{} -> {}. ", ask, ans)),
+            ];
+            message.extend(diff(guess.trim(), expected));
+            message.push(Line::raw(match distance {
+                1 => String::from(" (1 typo)"),
+                n => format!(" ({n} typos)"),
+            }));
+            message
+        }
+        Judgement::Wrong => vec![
+            Line::styled("Wrong! ", Style::default().fg(Color::Red)),
+            Line::raw(format!("{} -> {}. You guessed ", ask, ans)),
+            Line::styled(
+                guess,
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+        ],
+    }
+}
+
+fn feedback_revealed(ask: AppTerms<'_>, ans: AppTerms<'_>) -> Vec<Line<'static>> {
+    vec![
+        Line::styled("Shown! ", Style::default().fg(Color::Red)),
+        Line::raw(format!("{} -> {}", ask.join(", "), ans.join(", "))),
+    ]
+}
+
+fn feedback_overridden(ask: AppTerms<'_>, ans: AppTerms<'_>, accept: bool) -> Vec<Line<'static>> {
+    let mut message = vec![
+        Line::styled("Overridden! ", Style::default().fg(Color::Green)),
+        Line::raw(format!("{} -> {}", ask.join(", "), ans.join(", "))),
+    ];
+    if accept {
+        message.push(Line::styled(
+            " Your answer is accepted from now on",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    message
+}
+
+fn undid(terms: AppTerms<'_>) -> Vec<Line<'static>> {
+    vec![
+        Line::styled("Undone ", Style::default().fg(Color::Yellow)),
+        Line::raw(terms.join(", ")),
+    ]
+}
+
+fn skipped(ask: AppTerms<'_>) -> Vec<Line<'static>> {
+    vec![
+        Line::styled("Skipped ", Style::default().fg(Color::Yellow)),
+        Line::raw(ask.join(", ")),
+    ]
+}
+
+/// Shows the characters which should not have been typed in red, and the missing ones in green
+fn diff(guess: &str, expected: &str) -> Vec<Line<'static>> {
+    TextDiff::from_chars(guess, expected)
+        .iter_all_changes()
+        .map(|change| {
+            let style = match change.tag() {
+                ChangeTag::Equal => Style::default(),
+                ChangeTag::Delete => Style::default()
+                    .fg(Color::Red)
+                    .add_modifier(Modifier::CROSSED_OUT),
+                ChangeTag::Insert => Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::UNDERLINED),
+            };
+            Line::styled(change.value().to_string(), style)
+        })
+        .collect()
+}
+
 type AppTerms<'a> = &'a [Cow<'a, str>];
 struct App<'a> {
     message: &'a Text<'a>,
-    meta: &'a mut WordsMeta,
+    meta: &'a WordsMeta,
     /// The progress that has been made (stored in a string so you don't have to tostring it
     /// multiple times per word)
     n: &'a str,
@@ -199,33 +1022,108 @@ struct App<'a> {
     ans: AppTerms<'a>,
     term_lang: &'a str,
     def_lang: &'a str,
+    /// When the session ends, see [TryOptions::duration]
+    deadline: Option<Instant>,
+    /// Whether the previous answer was wrong and can be overridden
+    overridable: bool,
+    /// Whether there are answers which can be undone
+    undoable: bool,
+}
+
+/// What the user did when asked for a word
+enum Answer {
+    /// The user answered, how they were judged and how many hints they used
+    Given(Judgement, String, usize),
+    /// The user gave up and was shown the answer
+    Revealed,
+    /// The user wants to be asked the word again later
+    Skipped,
+    /// The user says the previous (wrong) answer was right, and whether it should be accepted
+    /// from now on
+    Override(bool),
+    /// The user wants to take back their previous answer
+    Undo,
+    /// The user pressed Ctrl-Q, progress should be saved by the caller
+    Quit,
+    /// The session's time ran out before the user answered
+    OutOfTime,
+}
+
+/// Waits for the next event. Returns `None` every second while there is a deadline, so the time
+/// left can be redrawn, and the caller should check whether the deadline has passed.
+fn next_event(deadline: Option<Instant>) -> Result<Option<Event>> {
+    let Some(deadline) = deadline else {
+        return Ok(Some(event::read()?));
+    };
+    let left = deadline.saturating_duration_since(Instant::now());
+    match !left.is_zero() && event::poll(left.min(Duration::from_secs(1)))? {
+        true => Ok(Some(event::read()?)),
+        false => Ok(None),
+    }
+}
+
+fn out_of_time(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|x| Instant::now() >= x)
+}
+
+/// Asks for the answer with `method`, `choices` gives the options for multiple choice
+fn ask_and_check<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: App<'_>,
+    method: &TryMethod,
+    config: &JudgementConfig,
+    choices: impl FnOnce(String) -> Vec<String>,
+) -> Result<Answer> {
+    match method {
+        TryMethod::Write => write_and_check(terminal, app, config),
+        TryMethod::Mpc => {
+            let options = choices(app.ans.join(", "));
+            choose_and_check(terminal, app, &options)
+        }
+    }
 }
 
 fn write_and_check<B: Backend>(
     terminal: &mut Terminal<B>,
     app: App<'_>,
-    list: &mut WordsList,
-    shuffle_map: &HashMap<usize, usize>,
-) -> Result<(bool, String)> {
+    config: &JudgementConfig,
+) -> Result<Answer> {
     let mut input: Input = String::new().into();
+    let mut hints = 0;
+    let answer = app.ans.first().map_or("", |x| x.as_ref());
     loop {
-        terminal.draw(|f| write_ui(f, &app, &input))?;
-        if let Event::Key(key) = event::read()? {
+        terminal.draw(|f| write_ui(f, &app, &input, hints))?;
+        let Some(event) = next_event(app.deadline)? else {
+            if out_of_time(app.deadline) {
+                return Ok(Answer::OutOfTime);
+            }
+            continue;
+        };
+        if let Event::Key(key) = event {
             match (key.code, key.modifiers) {
                 (KeyCode::Enter, _) => {
                     break;
                 }
                 (KeyCode::Char('q'), KeyModifiers::CONTROL) => {
-                    list.0.sort_unstable_by(|x, y| {
-                        x.times_answered_correctly.cmp(&y.times_answered_correctly)
-                    });
-                    app.meta.progress = Some(
-                        app.n
-                            .parse()
-                            .expect("Should always be a valid number given by callee"),
-                    );
-                    app.meta.shuffle_map = Some(shuffle_map.clone());
-                    return Err(eyre!("User quit"));
+                    return Ok(Answer::Quit);
+                }
+                (KeyCode::Tab, _) => {
+                    hints = (hints + 1).min(judgement::max_hints(answer));
+                }
+                (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                    return Ok(Answer::Revealed);
+                }
+                (KeyCode::Esc, _) => {
+                    return Ok(Answer::Skipped);
+                }
+                (KeyCode::Char('o'), KeyModifiers::CONTROL) if app.overridable => {
+                    return Ok(Answer::Override(false));
+                }
+                (KeyCode::Char('y'), KeyModifiers::CONTROL) if app.overridable => {
+                    return Ok(Answer::Override(true));
+                }
+                (KeyCode::Char('z'), KeyModifiers::CONTROL) if app.undoable => {
+                    return Ok(Answer::Undo);
                 }
                 _ => {
                     input_backend::to_input_request(&Event::Key(key)).and_then(|x| input.handle(x));
@@ -233,18 +1131,96 @@ fn write_and_check<B: Backend>(
             }
         }
     }
-    let res = check_word(&TryMethod::Write, input.value(), app.ans);
-    Ok((res, input.into()))
+    let judgement = check_word_with(&TryMethod::Write, config, input.value(), app.ans);
+    Ok(Answer::Given(judgement, input.into(), hints))
+}
+
+/// Picks up to 3 distractors from the other entries in the list, taken from the same side as the
+/// answer, and shuffles them together with the correct answer.
+fn mpc_options(
+    list: &WordsList,
+    index: usize,
+    swapped: bool,
+    correct: String,
+    rng: &mut impl Rng,
+) -> Vec<String> {
+    let mut options = list
+        .0
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != index)
+        .map(|(_, x)| match swapped {
+            false => x.definitions.join(", "),
+            true => x.terms.join(", "),
+        })
+        .filter(|x| *x != correct)
+        .unique()
+        .collect_vec()
+        .choose_multiple(rng, 3)
+        .cloned()
+        .collect_vec();
+    options.push(correct);
+    options.shuffle(rng);
+    options
+}
+
+fn choose_and_check<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: App<'_>,
+    options: &[String],
+) -> Result<Answer> {
+    let choice = loop {
+        terminal.draw(|f| mpc_ui(f, &app, options))?;
+        let Some(event) = next_event(app.deadline)? else {
+            if out_of_time(app.deadline) {
+                return Ok(Answer::OutOfTime);
+            }
+            continue;
+        };
+        if let Event::Key(key) = event {
+            match (key.code, key.modifiers) {
+                (KeyCode::Char('q'), KeyModifiers::CONTROL) => {
+                    return Ok(Answer::Quit);
+                }
+                (KeyCode::Char('z'), KeyModifiers::CONTROL) if app.undoable => {
+                    return Ok(Answer::Undo);
+                }
+                (KeyCode::Char(c), _) => {
+                    if let Some(choice) = c
+                        .to_digit(10)
+                        .and_then(|x| (x as usize).checked_sub(1))
+                        .and_then(|x| options.get(x))
+                    {
+                        break choice;
+                    }
+                }
+                _ => (),
+            }
+        }
+    };
+    Ok(Answer::Given(
+        check_word(&TryMethod::Mpc, choice, app.ans),
+        choice.clone(),
+        0,
+    ))
 }
 
-fn write_ui<'a>(f: &'a mut Frame, app: &'a App<'a>, input: &'a Input) {
+/// Renders the header with the progress and the previous message, and the word being asked
+fn header_and_ask<'a>(app: &'a App<'a>) -> (Paragraph<'a>, u16, Paragraph<'a>) {
     let bold = || Style::default().add_modifier(Modifier::BOLD);
     let header_msg = Text::from(vec![
-        Line::from(vec![
-            Span::raw(app.n),
-            Span::styled(" / ", bold()),
-            Span::raw(app.total_words.to_string()),
-        ]),
+        Line::from_iter(
+            [
+                Span::raw(app.n),
+                Span::styled(" / ", bold()),
+                Span::raw(app.total_words.to_string()),
+            ]
+            .into_iter()
+            .chain(app.deadline.map(|x| {
+                let left = x.saturating_duration_since(Instant::now()).as_secs();
+                Span::raw(format!(" ({}:{:02} left)", left / 60, left % 60))
+            })),
+        ),
         Line::from(vec![
             Span::raw("Direction: "),
             Span::styled(app.direction.to_string(), bold()),
@@ -257,17 +1233,20 @@ fn write_ui<'a>(f: &'a mut Frame, app: &'a App<'a>, input: &'a Input) {
             Span::raw("Definitions: "),
             Span::styled(app.def_lang, bold()),
         ]),
-        app.message.to_line(),
+        // Every line of the message is a part of the same line in the header
+        Line::from(
+            app.message
+                .lines
+                .iter()
+                .flat_map(|line| {
+                    line.spans
+                        .iter()
+                        .map(|span| span.clone().patch_style(line.style))
+                })
+                .collect_vec(),
+        ),
     ]);
-    let chunks = Layout::default()
-        .flex(Flex::Center)
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(header_msg.lines.len() as u16 + 2),
-            Constraint::Length(3),
-            Constraint::Length(3),
-        ])
-        .split(f.area());
+    let header_height = header_msg.lines.len() as u16 + 2;
     let header = Paragraph::new(header_msg)
         .block(
             Block::default()
@@ -284,10 +1263,49 @@ fn write_ui<'a>(f: &'a mut Frame, app: &'a App<'a>, input: &'a Input) {
     let ask = Paragraph::new(format!("{} ({})", app.ask.join(", "), lang))
         .block(Block::default().borders(Borders::ALL))
         .alignment(Alignment::Center);
+    (header, header_height, ask)
+}
+
+fn write_ui<'a>(f: &'a mut Frame, app: &'a App<'a>, input: &'a Input, hints: usize) {
+    let (header, header_height, ask) = header_and_ask(app);
+    let chunks = Layout::default()
+        .flex(Flex::Center)
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(header_height),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .split(f.area());
 
+    let title = match hints {
+        0 => String::from("Input"),
+        hints => format!(
+            "Input - hint: {} ({hints} {})",
+            judgement::hint(app.ans.first().map_or("", |x| x.as_ref()), hints),
+            match hints {
+                1 => "hint",
+                _ => "hints",
+            }
+        ),
+    };
     let input_view = Paragraph::new(input.to_span())
-        .block(Block::default().borders(Borders::ALL).title("Input"))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .wrap(Wrap { trim: true });
+    let keys = ["Tab: hint", "Ctrl-R: show answer", "Esc: skip"]
+        .into_iter()
+        .chain(
+            app.overridable
+                .then_some(["Ctrl-O: I was right", "Ctrl-Y: I was right, accept it"])
+                .into_iter()
+                .flatten(),
+        )
+        .chain(keys_help(app))
+        .join(", ");
+    let keys = Paragraph::new(keys)
+        .style(Style::default().add_modifier(Modifier::DIM))
+        .alignment(Alignment::Center);
     f.set_cursor_position((
         chunks[2].x + input.visual_cursor() as u16 + 1,
         chunks[2].y + 1,
@@ -296,4 +1314,125 @@ fn write_ui<'a>(f: &'a mut Frame, app: &'a App<'a>, input: &'a Input) {
     f.render_widget(header, chunks[0]);
     f.render_widget(ask, chunks[1]);
     f.render_widget(input_view, chunks[2]);
+    f.render_widget(keys, chunks[3]);
+}
+
+/// The keys which can be used with every method
+fn keys_help(app: &App) -> impl Iterator<Item = &'static str> {
+    app.undoable
+        .then_some("Ctrl-Z: undo")
+        .into_iter()
+        .chain(["Ctrl-Q: quit"])
+}
+
+fn mpc_ui<'a>(f: &'a mut Frame, app: &'a App<'a>, options: &'a [String]) {
+    let (header, header_height, ask) = header_and_ask(app);
+    let chunks = Layout::default()
+        .flex(Flex::Center)
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(header_height),
+            Constraint::Length(3),
+            Constraint::Length(options.len() as u16 + 2),
+            Constraint::Length(1),
+        ])
+        .split(f.area());
+
+    let options_view = Paragraph::new(Text::from(
+        options
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}. ", i + 1),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(x.as_str()),
+                ])
+            })
+            .collect_vec(),
+    ))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Choose (1-{})", options.len())),
+    )
+    .wrap(Wrap { trim: true });
+
+    let keys = Paragraph::new(keys_help(app).join(", "))
+        .style(Style::default().add_modifier(Modifier::DIM))
+        .alignment(Alignment::Center);
+
+    f.render_widget(header, chunks[0]);
+    f.render_widget(ask, chunks[1]);
+    f.render_widget(options_view, chunks[2]);
+    f.render_widget(keys, chunks[3]);
+}
+
+#[cfg(test)]
+mod tests {
+    use rusty_words_common::model::PrimitiveWordsList;
+
+    use super::*;
+
+    #[test]
+    fn test_ending() {
+        assert_eq!(Ending::check(0, 3, 0, None, false), None);
+        assert_eq!(
+            Ending::check(3, 3, 9, Some(9), true),
+            Some(Ending::Finished)
+        );
+        assert_eq!(Ending::check(1, 3, 5, Some(5), true), Some(Ending::Count));
+        assert_eq!(Ending::check(1, 3, 4, Some(5), false), None);
+        assert_eq!(Ending::check(1, 3, 4, Some(5), true), Some(Ending::Time));
+        assert_eq!(Ending::check(1, 3, 4, None, true), Some(Ending::Time));
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut summary = Summary::default();
+        summary.add(0, false, 0);
+        summary.add(1, false, 1);
+        summary.add(1, false, 0);
+        summary.add(2, false, 0);
+        summary.add(2, true, 2);
+        summary.add(3, true, 0);
+        summary.mastered.push(2);
+        assert_eq!(summary.answers.total, 6);
+        assert_eq!(summary.answers.correct, 2);
+        assert_eq!(summary.hints, 3);
+        // Entry 2 was learned in the end, so it isn't weak
+        assert_eq!(summary.weak(), [(1, 2), (0, 1)]);
+    }
+
+    #[test]
+    fn test_undo_accepted_override() {
+        let mut list =
+            WordsList::from(PrimitiveWordsList::try_from("kat\tcat\nhond\tdog\n").unwrap());
+        let original = list.clone();
+        let mut rotation: Rotation = (0..2).map(|i| (i, list.0[i].clone(), 0)).collect();
+        let (mut n, mut summary) = (0, Summary::default());
+
+        let before = Snapshot::take(&rotation, n, &summary, &list);
+        let (index, mut front, _) = rotation.pop_front().unwrap();
+        accept(&mut front, " puss ", false);
+        accept(&mut list.0[index], " puss ", false);
+        assert_eq!(list.0[0].definitions, ["cat", "puss"]);
+        list.0[index].answered(true, Utc::now(), Utc::now());
+        summary.add(index, true, 0);
+        n += 1;
+        let mut undo = vec![Snapshot {
+            guess: String::from(" puss "),
+            ..before
+        }];
+
+        undo.pop()
+            .unwrap()
+            .restore(&mut rotation, &mut n, &mut summary, &mut list);
+        assert!(list == original);
+        assert_eq!(rotation.iter().map(|(i, _, _)| *i).collect_vec(), [0, 1]);
+        assert!(rotation[0].1 == original.0[0]);
+        assert_eq!((n, summary.answers.total), (0, 0));
+    }
 }
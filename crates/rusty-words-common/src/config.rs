@@ -0,0 +1,34 @@
+//! Global configuration, stored in ~/.config/rusty-words/config.ron
+//!
+//! Every field has a default, so the file does not need to exist and can leave out anything the
+//! user does not want to change.
+
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{judgement::JudgementConfig, paths::config_file, store};
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The default for lists which do not have their own judgement config
+    pub judgement: JudgementConfig,
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        let path = config_file()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        store::read_ron(&path)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = config_file()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        store::write_ron(&path, self)
+    }
+}
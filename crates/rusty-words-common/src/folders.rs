@@ -0,0 +1,276 @@
+//! Folders to organise the lists in the index.
+//!
+//! Folders only exist in the index: a folder is a relative path like `school/french/ch3` stored in
+//! [crate::model::WordsMeta::folder], or in [WordsIndex::folders] if it was created without
+//! putting a list in it. A list without a folder is in the root folder.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Component, Path, PathBuf},
+};
+
+use color_eyre::{Result, eyre::eyre};
+
+use crate::model::WordsIndex;
+
+/// Turns a user given folder into the form it is stored in, or `None` for the root folder.
+/// Leading and trailing slashes and `.` are ignored.
+pub fn normalise(folder: &Path) -> Result<Option<PathBuf>> {
+    let mut normalised = PathBuf::new();
+    for component in folder.components() {
+        match component {
+            Component::Normal(x) => normalised.push(x),
+            Component::CurDir | Component::RootDir => (),
+            Component::ParentDir | Component::Prefix(_) => {
+                return Err(eyre!(
+                    "Invalid folder `{}`, folders can't contain `..` or drive letters.",
+                    folder.display()
+                ));
+            }
+        }
+    }
+    Ok(Some(normalised).filter(|x| !x.as_os_str().is_empty()))
+}
+
+/// Shows a folder with a trailing slash, or `/` for the root folder
+pub fn display(folder: Option<&Path>) -> String {
+    folder.map_or_else(|| String::from("/"), |x| format!("{}/", x.display()))
+}
+
+/// Whether `folder` is `parent` or one of its subfolders, everything is in the root folder
+pub fn is_within(folder: Option<&Path>, parent: Option<&Path>) -> bool {
+    match (folder, parent) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(folder), Some(parent)) => folder.starts_with(parent),
+    }
+}
+
+/// Moves `folder` from within `from` to within `to`, if it is within `from`
+fn rebase(folder: &Path, from: &Path, to: Option<&Path>) -> Option<Option<PathBuf>> {
+    let rest = folder.strip_prefix(from).ok()?;
+    let rebased = match to {
+        Some(to) => to.join(rest),
+        None => rest.to_path_buf(),
+    };
+    Some(Some(rebased).filter(|x| !x.as_os_str().is_empty()))
+}
+
+impl WordsIndex {
+    /// Every folder in the index, including the folders which only contain other folders
+    pub fn all_folders(&self) -> BTreeSet<PathBuf> {
+        let mut folders = BTreeSet::new();
+        for folder in self
+            .lists
+            .iter()
+            .filter_map(|x| x.folder.as_deref())
+            .chain(self.folders.iter().map(PathBuf::as_path))
+        {
+            folders.extend(
+                folder
+                    .ancestors()
+                    .filter(|x| !x.as_os_str().is_empty())
+                    .map(Path::to_path_buf),
+            );
+        }
+        folders
+    }
+
+    pub fn folder_exists(&self, folder: Option<&Path>) -> bool {
+        folder.is_none_or(|folder| self.all_folders().contains(folder))
+    }
+
+    fn _folder_not_found(folder: &Path) -> color_eyre::Report {
+        eyre!("Could not find folder `{}`", folder.display())
+    }
+
+    /// The ID's of the lists in `folder` and its subfolders
+    pub fn lists_in(&self, folder: Option<&Path>) -> Result<Vec<usize>> {
+        if !self.folder_exists(folder) {
+            return Err(Self::_folder_not_found(folder.unwrap_or(Path::new("/"))));
+        }
+        Ok(self
+            .lists
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| is_within(x.folder.as_deref(), folder))
+            .map(|(i, _)| i + 1)
+            .collect())
+    }
+
+    /// Creates a folder, returns false if it already existed
+    pub fn create_folder(&mut self, folder: &Path) -> bool {
+        if self.folder_exists(Some(folder)) {
+            return false;
+        }
+        self.folders.insert(folder.to_path_buf())
+    }
+
+    /// Moves the folder `from` and everything in it to `to`, merging it with `to` if that already
+    /// exists. Returns the amount of lists which were moved.
+    pub fn rename_folder(&mut self, from: &Path, to: Option<&Path>) -> Result<usize> {
+        if !self.folder_exists(Some(from)) {
+            return Err(Self::_folder_not_found(from));
+        }
+        if is_within(to, Some(from)) {
+            return Err(eyre!(
+                "Can't move folder `{}` into itself ({}).",
+                from.display(),
+                display(to)
+            ));
+        }
+
+        let mut moved = 0;
+        for meta in self.lists.iter_mut() {
+            if let Some(rebased) = meta.folder.as_deref().and_then(|x| rebase(x, from, to)) {
+                meta.folder = rebased;
+                moved += 1;
+            }
+        }
+        self.folders = std::mem::take(&mut self.folders)
+            .into_iter()
+            .filter_map(|x| match rebase(&x, from, to) {
+                Some(rebased) => rebased,
+                None => Some(x),
+            })
+            .collect();
+        if let Some(to) = to {
+            // Keep the folder around, even if nothing was in it
+            self.folders.insert(to.to_path_buf());
+        }
+        Ok(moved)
+    }
+
+    /// Removes a folder, moving its lists and subfolders to the folder it was in. Returns the
+    /// amount of lists which were moved.
+    pub fn remove_folder(&mut self, folder: &Path) -> Result<usize> {
+        let parent = folder.parent().filter(|x| !x.as_os_str().is_empty());
+        self.rename_folder(folder, parent)
+    }
+}
+
+/// The folders in the index with their lists, to show them as a tree
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct FolderTree {
+    /// The ID's of the lists directly in this folder
+    pub lists: Vec<usize>,
+    pub subfolders: BTreeMap<String, FolderTree>,
+}
+
+impl FolderTree {
+    pub fn new(index: &WordsIndex) -> Self {
+        let mut tree = Self::default();
+        for folder in index.all_folders() {
+            tree.get_mut(&folder);
+        }
+        for (i, meta) in index.lists.iter().enumerate() {
+            let folder = meta.folder.as_deref().unwrap_or(Path::new(""));
+            tree.get_mut(folder).lists.push(i + 1);
+        }
+        tree
+    }
+
+    fn get_mut(&mut self, folder: &Path) -> &mut Self {
+        folder.iter().fold(self, |tree, name| {
+            tree.subfolders
+                .entry(name.to_string_lossy().into_owned())
+                .or_default()
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lists.is_empty() && self.subfolders.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::WordsMeta;
+
+    fn index() -> WordsIndex {
+        let list = |name: &str, folder: Option<&str>| {
+            WordsMeta::new(name.to_string(), None, None, folder.map(PathBuf::from))
+        };
+        WordsIndex {
+            lists: vec![
+                list("a", None),
+                list("b", Some("school/french/ch3")),
+                list("c", Some("school/french")),
+                list("d", Some("school/german")),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn folder(x: &str) -> Option<PathBuf> {
+        Some(PathBuf::from(x))
+    }
+
+    #[test]
+    fn test_normalise() {
+        let normalise = |x: &str| normalise(Path::new(x)).unwrap();
+        assert_eq!(normalise("/school/./french/"), folder("school/french"));
+        assert_eq!(normalise("/"), None);
+        assert_eq!(normalise(""), None);
+        assert!(super::normalise(Path::new("school/../x")).is_err());
+    }
+
+    #[test]
+    fn test_lists_in() {
+        let index = index();
+        assert_eq!(index.lists_in(None).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(
+            index.lists_in(folder("school").as_deref()).unwrap(),
+            [2, 3, 4]
+        );
+        assert_eq!(
+            index.lists_in(folder("school/french").as_deref()).unwrap(),
+            [2, 3]
+        );
+        assert!(index.lists_in(folder("school/fr").as_deref()).is_err());
+    }
+
+    #[test]
+    fn test_rename_folder() {
+        let mut index = index();
+        let moved = index
+            .rename_folder(Path::new("school/french"), folder("french").as_deref())
+            .unwrap();
+        assert_eq!(moved, 2);
+        assert_eq!(index.lists[1].folder, folder("french/ch3"));
+        assert_eq!(index.lists[2].folder, folder("french"));
+        assert_eq!(index.lists[3].folder, folder("school/german"));
+        assert!(
+            index
+                .rename_folder(Path::new("french"), folder("french/x").as_deref())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_remove_folder() {
+        let mut index = index();
+        index.create_folder(Path::new("school/french/ch3/empty"));
+        index.remove_folder(Path::new("school/french")).unwrap();
+        assert_eq!(index.lists[1].folder, folder("school/ch3"));
+        assert_eq!(index.lists[2].folder, folder("school"));
+        assert!(index.folder_exists(folder("school/ch3/empty").as_deref()));
+        assert!(!index.folder_exists(folder("school/french").as_deref()));
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut index = index();
+        index.create_folder(Path::new("empty"));
+        let tree = FolderTree::new(&index);
+        assert_eq!(tree.lists, [1]);
+        assert_eq!(
+            tree.subfolders.keys().collect::<Vec<_>>(),
+            ["empty", "school"]
+        );
+        let french = &tree.subfolders["school"].subfolders["french"];
+        assert_eq!(french.lists, [3]);
+        assert_eq!(french.subfolders["ch3"].lists, [2]);
+    }
+}
@@ -0,0 +1,752 @@
+//! Importing and exporting words lists from and to other file formats.
+//!
+//! TSV and CSV only store the terms and definitions, optionally followed by the direction and the
+//! amount of times the entry was answered correctly. JSON and RON store the entries as-is.
+//!
+//! How TSV and CSV files are parsed can be changed with [ParseArgs]. Alternative terms or
+//! definitions are separated by commas or slashes, and can be quoted to contain those:
+//! `"and/or", "1,5"`. A quote inside quoted text is written twice.
+
+use std::{borrow::Cow, path::Path};
+
+use clap::{Args, ValueEnum};
+use color_eyre::{Help, Report, Result, eyre::eyre};
+use ron::ser::PrettyConfig;
+
+use crate::{
+    lang_codes::LanguageTag,
+    model::{DuplicateLine, PrimitiveWordsList, WordsList, parse_progress},
+};
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListFormat {
+    #[default]
+    Tsv,
+    Csv,
+    Json,
+    Ron,
+}
+
+impl ListFormat {
+    /// Guesses the format by the extension of the file, falling back to TSV
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .map(|x| x.to_string_lossy().to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("csv") => Self::Csv,
+            Some("json") => Self::Json,
+            Some("ron") => Self::Ron,
+            _ => Self::Tsv,
+        }
+    }
+}
+
+/// How TSV and CSV files are parsed when importing them
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+pub struct ParseArgs {
+    /// The separator between the terms, the definitions and the progress. A comma for CSV, and for
+    /// TSV a tab or `=` if the file doesn't contain any tabs. Use `\t` for a tab.
+    #[clap(long, value_parser = parse_separator)]
+    pub separator: Option<char>,
+    /// The characters which separate alternative terms or definitions, pass an empty string to
+    /// not split them
+    #[clap(long, default_value = ",/")]
+    pub alternatives: String,
+    /// The character to quote text containing separators with, pass an empty string to disable
+    /// quoting
+    #[clap(long, default_value = "\"", value_parser = parse_quote)]
+    pub quote: String,
+    /// Lines starting with this are skipped, pass an empty string to not skip any
+    #[clap(long, default_value = "#")]
+    pub comment: String,
+    /// Whether the first line contains the names of the columns and should be skipped
+    #[clap(value_enum, long, default_value = "auto")]
+    pub header: Header,
+    /// The encoding of the file, detected by its byte order mark if not given. Files without one
+    /// are read as UTF-8.
+    #[clap(value_enum, long)]
+    pub encoding: Option<Encoding>,
+    /// What to do with a line which has a term which is already on an earlier line
+    #[clap(value_enum, long, default_value = "merge")]
+    pub duplicates: Duplicates,
+}
+
+impl Default for ParseArgs {
+    fn default() -> Self {
+        Self {
+            separator: None,
+            alternatives: String::from(",/"),
+            quote: String::from("\""),
+            comment: String::from("#"),
+            header: Header::Auto,
+            encoding: None,
+            duplicates: Duplicates::Merge,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Header {
+    /// Skip the first line if it looks like `term, definition` or `French, English`
+    #[default]
+    Auto,
+    Yes,
+    No,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Duplicates {
+    /// Add the terms and definitions to the earlier line
+    #[default]
+    Merge,
+    /// Keep both lines as separate entries
+    Keep,
+    /// Stop importing
+    Fail,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    #[value(name = "utf-8")]
+    Utf8,
+    #[value(name = "utf-16le")]
+    Utf16Le,
+    #[value(name = "utf-16be")]
+    Utf16Be,
+    /// ISO 8859-1
+    #[value(name = "latin-1")]
+    Latin1,
+}
+
+fn parse_separator(s: &str) -> Result<char, String> {
+    let mut chars = s.chars();
+    match (s, chars.next(), chars.next()) {
+        ("\\t" | "tab", _, _) => Ok('\t'),
+        (_, Some(x), None) => Ok(x),
+        _ => Err(String::from("expected a single character or `\\t`")),
+    }
+}
+
+fn parse_quote(s: &str) -> Result<String, String> {
+    match s.chars().count() {
+        0 | 1 => Ok(s.to_string()),
+        _ => Err(String::from(
+            "expected a single character or an empty string",
+        )),
+    }
+}
+
+/// Column names which are recognised as a header
+const HEADER_WORDS: &[&str] = &[
+    "term",
+    "terms",
+    "word",
+    "words",
+    "definition",
+    "definitions",
+    "meaning",
+    "meanings",
+    "translation",
+    "translations",
+    "question",
+    "answer",
+    "front",
+    "back",
+];
+
+impl ParseArgs {
+    fn quote(&self) -> Option<char> {
+        self.quote.chars().next()
+    }
+
+    fn is_comment(&self, line: &str) -> bool {
+        !self.comment.is_empty() && line.trim_start().starts_with(self.comment.as_str())
+    }
+
+    fn is_header(&self, fields: &[Vec<String>]) -> bool {
+        let is_name = |field: &Vec<String>| {
+            let [name] = &field[..] else {
+                return false;
+            };
+            HEADER_WORDS.contains(&name.to_lowercase().as_str())
+                || name.parse::<LanguageTag>().is_ok_and(|x| {
+                    x.script.is_none()
+                        && x.region.is_none()
+                        && x.language.to_name().eq_ignore_ascii_case(name)
+                })
+        };
+        match self.header {
+            Header::Yes => true,
+            Header::No => false,
+            Header::Auto => fields.len() >= 2 && fields[..2].iter().all(is_name),
+        }
+    }
+
+    /// Splits a line into fields on `separator`, and every field into alternatives. Quoted text
+    /// is kept together, a quote only starts quoted text at the start of an alternative.
+    fn split_line(&self, line: &str, separator: Option<char>) -> Result<Vec<Vec<String>>, String> {
+        let quote = self.quote();
+        let mut fields = Vec::new();
+        let mut alternatives = Vec::new();
+        let mut chars = line.chars().peekable();
+        loop {
+            let mut current = String::new();
+            while chars
+                .next_if(|&x| x.is_whitespace() && Some(x) != separator)
+                .is_some()
+            {}
+            let quoted = quote.is_some_and(|quote| chars.next_if_eq(&quote).is_some());
+            if let (true, Some(quote)) = (quoted, quote) {
+                loop {
+                    match chars.next() {
+                        Some(x) if x == quote => match chars.next_if_eq(&quote) {
+                            Some(_) => current.push(quote),
+                            None => break,
+                        },
+                        Some(x) => current.push(x),
+                        None => return Err(String::from("A quote is never closed")),
+                    }
+                }
+            }
+            let quoted_len = current.len();
+            let end_of_field = loop {
+                match chars.next() {
+                    Some(x) if Some(x) == separator => break Some(true),
+                    Some(x) if self.alternatives.contains(x) => break Some(false),
+                    Some(x) => current.push(x),
+                    None => break None,
+                }
+            };
+            let trimmed = quoted_len + current[quoted_len..].trim_end().len();
+            current.truncate(trimmed);
+            if !current.is_empty() {
+                alternatives.push(current);
+            }
+            match end_of_field {
+                Some(false) => (),
+                Some(true) => fields.push(std::mem::take(&mut alternatives)),
+                None => {
+                    fields.push(alternatives);
+                    return Ok(fields);
+                }
+            }
+        }
+    }
+
+    /// Quotes `value` if it would not be parsed back as a single alternative otherwise
+    fn quote_value<'a>(&self, value: &'a str, separator: char) -> Cow<'a, str> {
+        let Some(quote) = self.quote() else {
+            return Cow::Borrowed(value);
+        };
+        let needs_quotes = value.starts_with(quote)
+            || value.contains(|x| x == separator || x == '\n' || self.alternatives.contains(x))
+            || value.trim() != value
+            || self.is_comment(value);
+        match needs_quotes {
+            true => Cow::Owned(format!(
+                "{quote}{}{quote}",
+                value.replace(quote, &format!("{quote}{quote}"))
+            )),
+            false => Cow::Borrowed(value),
+        }
+    }
+
+    /// Joins alternatives so they are parsed back the same way
+    pub fn join_alternatives<S: AsRef<str>>(&self, values: &[S], separator: char) -> String {
+        values
+            .iter()
+            .map(|x| self.quote_value(x.as_ref(), separator))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Decodes an imported file, removing the byte order mark if there is one
+pub fn decode(bytes: &[u8], encoding: Option<Encoding>) -> Result<String> {
+    use Encoding::*;
+    let (encoding, bytes) = match (encoding, bytes) {
+        (None | Some(Utf8), [0xEF, 0xBB, 0xBF, rest @ ..]) => (Utf8, rest),
+        (None | Some(Utf16Le), [0xFF, 0xFE, rest @ ..]) => (Utf16Le, rest),
+        (None | Some(Utf16Be), [0xFE, 0xFF, rest @ ..]) => (Utf16Be, rest),
+        (encoding, bytes) => (encoding.unwrap_or(Utf8), bytes),
+    };
+    let suggestion = "Use `--encoding` to set the encoding of the file, like `--encoding latin-1`.";
+    match encoding {
+        Utf8 => String::from_utf8(bytes.to_vec()).map_err(|e| {
+            let valid = &bytes[..e.utf8_error().valid_up_to()];
+            let line = valid.iter().filter(|&&x| x == b'\n').count() + 1;
+            eyre!("The file is not valid UTF-8, line {line} contains an invalid character")
+                .with_suggestion(|| suggestion)
+        }),
+        Utf16Le | Utf16Be => {
+            let units = bytes
+                .chunks(2)
+                .map(|x| match (encoding, x) {
+                    (Utf16Le, &[a, b]) => Ok(u16::from_le_bytes([a, b])),
+                    (_, &[a, b]) => Ok(u16::from_be_bytes([a, b])),
+                    _ => Err(eyre!("The file is not valid UTF-16, it has an odd length")),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            String::from_utf16(&units).map_err(|e| {
+                eyre!("The file is not valid UTF-16: {e}").with_suggestion(|| suggestion)
+            })
+        }
+        Latin1 => Ok(bytes.iter().map(|&x| char::from(x)).collect()),
+    }
+}
+
+/// Formats the list, resetting the progress of every entry unless `progress` is set. TSV and CSV
+/// only contain the direction if `progress` is set, and start with a header so the first entry is
+/// never mistaken for one when the list is imported again.
+pub fn export(list: &WordsList, format: ListFormat, progress: bool) -> Result<String> {
+    let stripped;
+    let list = if progress {
+        list
+    } else {
+        let mut list = list.clone();
+        for entry in list.0.iter_mut() {
+            entry.times_answered_correctly = 0;
+            entry.times_answered_wrong = 0;
+            entry.last_seen = None;
+            entry.review = None;
+            entry.last_session = None;
+        }
+        stripped = list;
+        &stripped
+    };
+
+    let args = ParseArgs::default();
+    let header = match progress {
+        true => &["Term", "Definition", "Direction", "Correct"][..],
+        false => &["Term", "Definition"][..],
+    };
+    Ok(match format {
+        ListFormat::Tsv if !progress => header.join("\t") + "\n" + &list.to_tsv(),
+        ListFormat::Tsv => std::iter::once(header.join("\t") + "\n")
+            .chain(list.0.iter().map(|x| {
+                format!(
+                    "{}\t{}\t{:?}\t{}\n",
+                    args.join_alternatives(&x.terms, '\t'),
+                    args.join_alternatives(&x.definitions, '\t'),
+                    x.direction,
+                    x.times_answered_correctly
+                )
+            }))
+            .collect(),
+        ListFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .flexible(true)
+                .from_writer(Vec::new());
+            writer.write_record(header)?;
+            for x in &list.0 {
+                let mut record = vec![
+                    args.join_alternatives(&x.terms, ','),
+                    args.join_alternatives(&x.definitions, ','),
+                ];
+                if progress {
+                    record.push(format!("{:?}", x.direction));
+                    record.push(x.times_answered_correctly.to_string());
+                }
+                writer.write_record(record)?;
+            }
+            String::from_utf8(writer.into_inner()?)?
+        }
+        ListFormat::Json => serde_json::to_string_pretty(list)? + "\n",
+        ListFormat::Ron => ron::ser::to_string_pretty(list, PrettyConfig::default())? + "\n",
+    })
+}
+
+pub fn import<'a>(
+    data: &'a str,
+    format: ListFormat,
+    args: &ParseArgs,
+) -> Result<(WordsList<'a>, Vec<DuplicateLine>)> {
+    let parsed = match format {
+        ListFormat::Tsv => parse_tsv(data, args)?,
+        ListFormat::Csv => parse_csv(data, args)?,
+        ListFormat::Json => return Ok((serde_json::from_str(data)?, Vec::new())),
+        ListFormat::Ron => return Ok((ron::de::from_str(data)?, Vec::new())),
+    };
+    let duplicate_lines = parsed.duplicate_lines().to_vec();
+    Ok((WordsList::from(parsed), duplicate_lines))
+}
+
+fn line_error(n: usize, line: &str, reason: &str) -> Report {
+    eyre!("Couldn't parse line number {n}: {reason}").with_note(|| format!("The line is: {line}"))
+}
+
+/// Adds line `n`, which has been split into fields, to the list
+fn insert_fields(
+    list: &mut PrimitiveWordsList,
+    n: usize,
+    fields: Vec<Vec<String>>,
+) -> Result<(), String> {
+    let mut fields = fields.into_iter();
+    let terms = fields.next().unwrap_or_default();
+    let definitions = fields.next().unwrap_or_default();
+    if terms.is_empty() {
+        return Err(String::from("Definition needs term"));
+    }
+    if definitions.is_empty() {
+        return Err(String::from("Term needs definition"));
+    }
+    let mut column = || fields.next().and_then(|x| x.into_iter().next());
+    let (direction, times) = (column(), column());
+    let progress = parse_progress(direction.as_deref(), times.as_deref());
+    list.insert(
+        n,
+        terms.into_iter().map(Cow::Owned).collect(),
+        definitions.into_iter().map(Cow::Owned).collect(),
+        progress,
+    )
+}
+
+pub fn parse_tsv<'a>(data: &str, args: &ParseArgs) -> Result<PrimitiveWordsList<'a>> {
+    let separator = args.separator.unwrap_or_else(|| match data.contains('\t') {
+        true => '\t',
+        false => '=',
+    });
+    let mut list = PrimitiveWordsList::new(args.duplicates);
+    let mut first = true;
+    for (n, line) in data.lines().enumerate() {
+        if line.trim().is_empty() || args.is_comment(line) {
+            continue;
+        }
+        let error = |reason: String| line_error(n + 1, line, &reason);
+        let fields = args.split_line(line, Some(separator)).map_err(error)?;
+        if std::mem::take(&mut first) && args.is_header(&fields) {
+            continue;
+        }
+        insert_fields(&mut list, n + 1, fields).map_err(error)?;
+    }
+    Ok(list)
+}
+
+fn parse_csv<'a>(data: &str, args: &ParseArgs) -> Result<PrimitiveWordsList<'a>> {
+    let ascii = |x: char, what: &str| {
+        u8::try_from(x)
+            .ok()
+            .filter(u8::is_ascii)
+            .ok_or_else(|| eyre!("The {what} of a CSV file must be an ASCII character, not `{x}`"))
+    };
+    let separator = ascii(args.separator.unwrap_or(','), "separator")?;
+    let quote = args.quote().map(|x| ascii(x, "quote")).transpose()?;
+    // Comments are blanked out instead of removed, so the line numbers stay the same
+    let lines = data
+        .lines()
+        .map(|x| if args.is_comment(x) { "" } else { x })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(separator)
+        .quoting(quote.is_some())
+        .quote(quote.unwrap_or(b'"'))
+        .from_reader(lines.as_bytes());
+
+    // The positions of the reader are before the empty lines it skips, so skip them as well
+    let line_number = |position: Option<&csv::Position>| {
+        let start = position.map_or(0, |x| x.byte() as usize).min(lines.len());
+        let empty = lines[start..].bytes().take_while(|&x| x == b'\n').count();
+        lines[..start + empty]
+            .bytes()
+            .filter(|&x| x == b'\n')
+            .count()
+            + 1
+    };
+
+    let mut list = PrimitiveWordsList::new(args.duplicates);
+    let mut first = true;
+    for record in reader.records() {
+        let record = record.map_err(|e| {
+            eyre!(
+                "Couldn't parse line number {}: {e}",
+                line_number(e.position())
+            )
+        })?;
+        let n = line_number(record.position());
+        let line = data.lines().nth(n - 1).unwrap_or_default();
+        let fields = record
+            .iter()
+            .map(|x| args.split_line(x, None).map(|mut x| x.remove(0)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| line_error(n, line, &e))?;
+        if std::mem::take(&mut first) && args.is_header(&fields) {
+            continue;
+        }
+        insert_fields(&mut list, n, fields).map_err(|e| line_error(n, line, &e))?;
+    }
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        model::{WordsDirection, WordsEntry},
+        scheduler::ReviewState,
+    };
+    use chrono::{DateTime, Utc};
+
+    fn list() -> WordsList<'static> {
+        let entry = |term: &'static str, definitions: &[&'static str], times| WordsEntry {
+            terms: vec![Cow::Borrowed(term)],
+            definitions: definitions.iter().map(|&x| Cow::Borrowed(x)).collect(),
+            direction: WordsDirection::DT,
+            times_answered_correctly: times,
+            times_answered_wrong: 0,
+            last_seen: None,
+            review: None,
+            last_session: None,
+        };
+        WordsList(vec![
+            entry("bank", &["sofa", "bank"], 2),
+            entry("hond", &["dog"], 0),
+            entry("\"kat\"", &["cat"], 5),
+            entry("en/of", &["and/or", "1,5", " a = b"], 1),
+        ])
+    }
+
+    #[test]
+    fn test_round_trip_progress() {
+        for format in [
+            ListFormat::Tsv,
+            ListFormat::Csv,
+            ListFormat::Json,
+            ListFormat::Ron,
+        ] {
+            let exported = export(&list(), format, true).unwrap();
+            let (imported, _) = import(&exported, format, &ParseArgs::default()).unwrap();
+            assert!(imported == list(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip_without_progress() {
+        for format in [ListFormat::Tsv, ListFormat::Csv] {
+            let exported = export(&list(), format, false).unwrap();
+            let (imported, _) = import(&exported, format, &ParseArgs::default()).unwrap();
+            for (x, y) in imported.0.iter().zip(list().0.iter()) {
+                assert_eq!(x.terms, y.terms);
+                assert_eq!(x.definitions, y.definitions);
+                assert_eq!(x.direction, WordsDirection::Auto);
+                assert_eq!(x.times_answered_correctly, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_strips_progress() {
+        let seen = "2026-10-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let session = "2026-10-02T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut list = list();
+        list.0[0] = WordsEntry {
+            times_answered_wrong: 3,
+            last_seen: Some(seen),
+            review: Some(ReviewState::new(seen)),
+            last_session: Some((session, true)),
+            ..list.0[0].clone()
+        };
+        for format in [ListFormat::Json, ListFormat::Ron] {
+            let exported = export(&list, format, false).unwrap();
+            for date in [seen, session] {
+                assert!(!exported.contains(&date.to_string()), "{format:?}");
+            }
+            let (imported, _) = import(&exported, format, &ParseArgs::default()).unwrap();
+            for x in &imported.0 {
+                assert_eq!(
+                    (x.times_answered_correctly, x.times_answered_wrong),
+                    (0, 0),
+                    "{format:?}"
+                );
+                assert_eq!(x.last_seen, None, "{format:?}");
+                assert_eq!(x.review, None, "{format:?}");
+                assert_eq!(x.last_session, None, "{format:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_header_like_entry() {
+        let mut list = list();
+        list.0[0].terms = vec![Cow::Borrowed("English")];
+        list.0[0].definitions = vec![Cow::Borrowed("French")];
+        for format in [ListFormat::Tsv, ListFormat::Csv] {
+            let exported = export(&list, format, true).unwrap();
+            let (imported, _) = import(&exported, format, &ParseArgs::default()).unwrap();
+            assert!(imported == list, "{format:?}");
+            let exported = export(&list, format, false).unwrap();
+            let (imported, _) = import(&exported, format, &ParseArgs::default()).unwrap();
+            assert_eq!(imported.0.len(), list.0.len(), "{format:?}");
+            assert_eq!(imported.0[0].terms, ["English"], "{format:?}");
+        }
+
+        // Editing a list parses it without a header
+        let edited = PrimitiveWordsList::try_from("term\tdefinition\nkat\tcat\n").unwrap();
+        assert_eq!(WordsList::from(edited).0.len(), 2);
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(ListFormat::from_path(Path::new("a.CSV")), ListFormat::Csv);
+        assert_eq!(ListFormat::from_path(Path::new("a.json")), ListFormat::Json);
+        assert_eq!(ListFormat::from_path(Path::new("a.ron")), ListFormat::Ron);
+        assert_eq!(ListFormat::from_path(Path::new("a.txt")), ListFormat::Tsv);
+        assert_eq!(ListFormat::from_path(Path::new("a")), ListFormat::Tsv);
+    }
+
+    fn parse(data: &str, format: ListFormat, args: &ParseArgs) -> Vec<(String, Vec<String>)> {
+        let mut entries = import(data, format, args)
+            .unwrap()
+            .0
+            .0
+            .into_iter()
+            .map(|x| {
+                let definitions = x.definitions.iter().map(|x| x.to_string()).collect();
+                (x.terms.join(", "), definitions)
+            })
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries
+    }
+
+    fn entry(term: &str, definitions: &[&str]) -> (String, Vec<String>) {
+        let definitions = definitions.iter().map(|x| x.to_string()).collect();
+        (term.to_string(), definitions)
+    }
+
+    #[test]
+    fn test_parse_tsv() {
+        let data = "\u{feff}Term\tDefinition\r\n# a comment\n\nbank\tsofa/ bank\r\n\"a = b\"\t\"1,5\", \"say \"\"hi\"\"\"\n";
+        let args = ParseArgs::default();
+        let data = decode(data.as_bytes(), None).unwrap();
+        assert_eq!(
+            parse(&data, ListFormat::Tsv, &args),
+            [
+                entry("a = b", &["1,5", "say \"hi\""]),
+                entry("bank", &["sofa", "bank"])
+            ]
+        );
+
+        // Without tabs `=` is the separator, and a header is only skipped if it looks like one
+        assert_eq!(
+            parse("hond = dog\nkat = cat", ListFormat::Tsv, &args),
+            [entry("hond", &["dog"]), entry("kat", &["cat"])]
+        );
+        let args = ParseArgs {
+            separator: Some(';'),
+            alternatives: String::new(),
+            quote: String::new(),
+            comment: String::from("//"),
+            header: Header::Yes,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse("x;y\n// z;z\n\"a;b, c", ListFormat::Tsv, &args),
+            [entry("\"a", &["b, c"])]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let data =
+            "French,English\n# comment\nchat,\"cat, \"\"puss\"\"\"\n\"l'eau\",\"\"\"and/or\"\"\"\n";
+        assert_eq!(
+            parse(data, ListFormat::Csv, &ParseArgs::default()),
+            [entry("chat", &["cat", "puss"]), entry("l'eau", &["and/or"])]
+        );
+    }
+
+    #[test]
+    fn test_line_errors() {
+        let error = |data: &str, format| {
+            import(data, format, &ParseArgs::default())
+                .err()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            error("a\tb\n# x\nc\n", ListFormat::Tsv),
+            "Couldn't parse line number 3: Term needs definition"
+        );
+        assert_eq!(
+            error("a\tb\n\"c\td\n", ListFormat::Tsv),
+            "Couldn't parse line number 2: A quote is never closed"
+        );
+        assert_eq!(
+            error("a,b\n\n,c\n", ListFormat::Csv),
+            "Couldn't parse line number 3: Definition needs term"
+        );
+    }
+
+    #[test]
+    fn test_order_and_duplicates() {
+        let data = "kat, poes\tcat\nhond\tdog\npoes\tpuss, cat\n";
+        let terms = |args: &ParseArgs| {
+            let (list, _) = import(data, ListFormat::Tsv, args).unwrap();
+            list.0
+                .iter()
+                .map(|x| (x.terms.join(", "), x.definitions.join(", ")))
+                .collect::<Vec<_>>()
+        };
+        let pairs = |x: &[(&str, &str)]| {
+            x.iter()
+                .map(|&(x, y)| (x.to_string(), y.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            terms(&ParseArgs::default()),
+            pairs(&[("kat, poes", "cat, puss"), ("hond", "dog")])
+        );
+        let args = |duplicates| ParseArgs {
+            duplicates,
+            ..Default::default()
+        };
+        assert_eq!(
+            terms(&args(Duplicates::Keep)),
+            pairs(&[("kat, poes", "cat"), ("hond", "dog"), ("poes", "puss, cat")])
+        );
+        let (_, duplicate_lines) = import(data, ListFormat::Tsv, &args(Duplicates::Keep)).unwrap();
+        assert_eq!(
+            duplicate_lines,
+            [DuplicateLine {
+                line: 3,
+                earlier: 1,
+                terms: String::from("poes"),
+                merged: false,
+            }]
+        );
+        assert_eq!(
+            duplicate_lines[0].to_string(),
+            "`poes` on line 3 is already on line 1, kept both"
+        );
+        let error = import(data, ListFormat::Tsv, &args(Duplicates::Fail))
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            error,
+            "Couldn't parse line number 3: `poes` is already on line 1"
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"\xEF\xBB\xBFa\tb", None).unwrap(), "a\tb");
+        assert_eq!(decode(b"\xFF\xFEa\0\xE9\0", None).unwrap(), "a\u{e9}");
+        assert_eq!(
+            decode(b"\xFE\xFF\0a\0\xE9", Some(Encoding::Utf16Be)).unwrap(),
+            "a\u{e9}"
+        );
+        assert_eq!(
+            decode(b"\xE9t\xE9", Some(Encoding::Latin1)).unwrap(),
+            "\u{e9}t\u{e9}"
+        );
+        let error = decode(b"a\nb\xE9", None).unwrap_err().to_string();
+        assert!(error.contains("line 2"), "{error}");
+    }
+}
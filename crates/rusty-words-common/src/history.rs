@@ -0,0 +1,363 @@
+//! History of every answer given while practicing, stored in
+//! ~/.local/share/rusty-words/history.log
+//!
+//! The file contains one [HistoryEntry] serialized as RON per line, so it can be appended to
+//! without having to read it first.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use color_eyre::{
+    Result,
+    eyre::{Context, eyre},
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use uuid::Uuid;
+
+use crate::{model::WordsDirection, paths::history_file};
+
+#[serde_as]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    #[serde_as(as = "DisplayFromStr")]
+    pub list: Uuid,
+    /// The terms of the entry, which identify it within the list
+    pub terms: Vec<String>,
+    /// The direction the entry was asked in, either TD or DT
+    pub direction: WordsDirection,
+    pub guess: String,
+    pub correct: bool,
+    /// How long it took to answer, in milliseconds
+    pub response_ms: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub timestamp: DateTime<Utc>,
+    /// When the session this answer was given in started, which identifies the session
+    #[serde_as(as = "DisplayFromStr")]
+    pub session: DateTime<Utc>,
+}
+
+/// Appends the answers of a single session to the history file
+pub struct HistoryLog {
+    file: File,
+    session: DateTime<Utc>,
+    /// The length of the file before each answer recorded in this session
+    recorded: Vec<u64>,
+    /// The length of the file after the last answer was recorded
+    len: u64,
+}
+
+impl HistoryLog {
+    pub fn open() -> Result<Self> {
+        Self::open_at(&history_file()?)
+    }
+
+    fn open_at(path: &Path) -> Result<Self> {
+        let mut file = File::options().create(true).append(true).open(path)?;
+        // Don't append to a line which was cut off, so only that line is lost
+        let mut last = [b'\n'];
+        let mut reader = File::open(path)?;
+        if reader.seek(SeekFrom::End(0))? > 0 {
+            reader.seek(SeekFrom::End(-1))?;
+            reader.read_exact(&mut last)?;
+        }
+        if last != [b'\n'] {
+            writeln!(&mut file)?;
+        }
+        Ok(Self {
+            file,
+            session: Utc::now(),
+            recorded: Vec::new(),
+            len: 0,
+        })
+    }
+
+    pub fn session(&self) -> DateTime<Utc> {
+        self.session
+    }
+
+    pub fn record(&mut self, entry: &HistoryEntry) -> Result<()> {
+        let ser = ron::ser::to_string(entry)?;
+        self.recorded.push(self.file.metadata()?.len());
+        writeln!(&mut self.file, "{ser}")?;
+        self.len = self.file.metadata()?.len();
+        Ok(())
+    }
+
+    /// Removes the last answer recorded in this session, returns false if there was none
+    pub fn undo(&mut self) -> Result<bool> {
+        let Some(&len) = self.recorded.last() else {
+            return Ok(false);
+        };
+        // Truncating would remove the answers another session recorded after this one
+        if self.file.metadata()?.len() != self.len {
+            return Err(eyre!(
+                "another session has recorded answers since, so the answer stays in the history"
+            ));
+        }
+        self.file.set_len(len)?;
+        self.recorded.pop();
+        self.len = len;
+        Ok(true)
+    }
+}
+
+/// Reads the whole history, returns an empty history if nothing has been recorded yet. Lines which
+/// can't be read (like a line which was cut off by a crash) are skipped, the amount of skipped lines
+/// is returned as well.
+pub fn load() -> Result<(Vec<HistoryEntry>, usize)> {
+    load_from(&history_file()?)
+}
+
+fn load_from(path: &Path) -> Result<(Vec<HistoryEntry>, usize)> {
+    if !path.exists() {
+        return Ok((Vec::new(), 0));
+    }
+    let data = std::fs::read(path).with_context(|| format!("while reading {}", path.display()))?;
+    Ok(parse(&String::from_utf8_lossy(&data)))
+}
+
+fn parse(data: &str) -> (Vec<HistoryEntry>, usize) {
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for line in data.lines().filter(|x| !x.trim().is_empty()) {
+        match ron::de::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => skipped += 1,
+        }
+    }
+    (entries, skipped)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Accuracy {
+    pub correct: usize,
+    pub total: usize,
+}
+
+impl Accuracy {
+    pub fn add(&mut self, correct: bool) {
+        self.correct += correct as usize;
+        self.total += 1;
+    }
+
+    pub fn wrong(&self) -> usize {
+        self.total - self.correct
+    }
+
+    pub fn percentage(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            total => self.correct as f64 / total as f64 * 100.0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SessionStats {
+    pub start: DateTime<Utc>,
+    /// The time between the start of the session and the last answer
+    pub duration: Duration,
+    pub accuracy: Accuracy,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Stats {
+    pub total: Accuracy,
+    /// Grouped by the local date
+    pub per_day: BTreeMap<NaiveDate, Accuracy>,
+    /// Every entry that was answered wrong at least once, most mistakes first
+    pub hardest: Vec<(Uuid, Vec<String>, Accuracy)>,
+    /// Oldest first
+    pub sessions: Vec<SessionStats>,
+}
+
+impl Stats {
+    pub fn new<'a>(history: impl IntoIterator<Item = &'a HistoryEntry>) -> Self {
+        let mut total = Accuracy::default();
+        let mut per_day = BTreeMap::<_, Accuracy>::new();
+        let mut per_entry = HashMap::<_, Accuracy>::new();
+        let mut sessions = BTreeMap::<_, (DateTime<Utc>, Accuracy)>::new();
+        for entry in history {
+            total.add(entry.correct);
+            per_day
+                .entry(entry.timestamp.with_timezone(&Local).date_naive())
+                .or_default()
+                .add(entry.correct);
+            per_entry
+                .entry((entry.list, entry.terms.clone()))
+                .or_default()
+                .add(entry.correct);
+            let (end, accuracy) = sessions
+                .entry(entry.session)
+                .or_insert((entry.session, Accuracy::default()));
+            *end = (*end).max(entry.timestamp);
+            accuracy.add(entry.correct);
+        }
+
+        let mut hardest = per_entry
+            .into_iter()
+            .filter(|(_, x)| x.wrong() > 0)
+            .map(|((list, terms), x)| (list, terms, x))
+            .collect::<Vec<_>>();
+        hardest.sort_by(|(_, x_terms, x), (_, y_terms, y)| {
+            y.wrong()
+                .cmp(&x.wrong())
+                .then(x.correct.cmp(&y.correct))
+                .then(x_terms.cmp(y_terms))
+        });
+
+        let sessions = sessions
+            .into_iter()
+            .map(|(start, (end, accuracy))| SessionStats {
+                start,
+                duration: (end - start).to_std().unwrap_or_default(),
+                accuracy,
+            })
+            .collect();
+
+        Self {
+            total,
+            per_day,
+            hardest,
+            sessions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn test_stats() {
+        let list = Uuid::nil();
+        let session = Utc::now();
+        let answer = |term: &str, correct, seconds| HistoryEntry {
+            list,
+            terms: vec![term.to_string()],
+            direction: WordsDirection::TD,
+            guess: String::new(),
+            correct,
+            response_ms: 1000,
+            timestamp: session + TimeDelta::seconds(seconds),
+            session,
+        };
+        let history = [
+            answer("hond", false, 1),
+            answer("hond", false, 2),
+            answer("kat", false, 3),
+            answer("kat", true, 4),
+            answer("hond", true, 5),
+            answer("muis", true, 6),
+        ];
+        let stats = Stats::new(&history);
+
+        assert_eq!(
+            stats.total,
+            Accuracy {
+                correct: 3,
+                total: 6
+            }
+        );
+        assert_eq!(stats.total.percentage(), 50.0);
+        let hardest = stats
+            .hardest
+            .iter()
+            .map(|(_, terms, x)| (terms[0].as_str(), x.wrong()))
+            .collect::<Vec<_>>();
+        assert_eq!(hardest, [("hond", 2), ("kat", 1)]);
+        assert_eq!(stats.sessions.len(), 1);
+        assert_eq!(stats.sessions[0].duration, Duration::from_secs(6));
+    }
+
+    #[test]
+    fn test_undo() {
+        let dir = TempDir::new("history");
+        let path = dir.join("history.log");
+        let answer = |term: &str, session| HistoryEntry {
+            list: Uuid::nil(),
+            terms: vec![term.to_string()],
+            direction: WordsDirection::TD,
+            guess: String::new(),
+            correct: true,
+            response_ms: 1000,
+            timestamp: session,
+            session,
+        };
+        let terms = || {
+            let (history, skipped) = load_from(&path).unwrap();
+            assert_eq!(skipped, 0);
+            history
+                .into_iter()
+                .map(|x| x.terms[0].clone())
+                .collect::<Vec<_>>()
+        };
+
+        let mut log = HistoryLog::open_at(&path).unwrap();
+        log.record(&answer("hond", log.session())).unwrap();
+        log.record(&answer("kat", log.session())).unwrap();
+        assert!(log.undo().unwrap());
+        assert_eq!(terms(), ["hond"]);
+        log.record(&answer("muis", log.session())).unwrap();
+        assert_eq!(terms(), ["hond", "muis"]);
+
+        // The answers of another session are never removed
+        let mut other = HistoryLog::open_at(&path).unwrap();
+        other.record(&answer("vis", other.session())).unwrap();
+        assert!(log.undo().is_err());
+        assert_eq!(terms(), ["hond", "muis", "vis"]);
+        assert!(other.undo().unwrap());
+        assert!(!other.undo().unwrap());
+        assert!(log.undo().unwrap());
+        assert!(log.undo().unwrap());
+        assert!(!log.undo().unwrap());
+        assert!(terms().is_empty());
+    }
+
+    #[test]
+    fn test_serialize_single_line() {
+        let entry = HistoryEntry {
+            list: Uuid::nil(),
+            terms: vec![String::from("a\nb")],
+            direction: WordsDirection::DT,
+            guess: String::from("c"),
+            correct: false,
+            response_ms: 10,
+            timestamp: Utc::now(),
+            session: Utc::now(),
+        };
+        let ser = ron::ser::to_string(&entry).unwrap();
+        assert!(!ser.contains('\n'));
+        assert_eq!(ron::de::from_str::<HistoryEntry>(&ser).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_parse_skips_bad_lines() {
+        let entry = HistoryEntry {
+            list: Uuid::nil(),
+            terms: vec![String::from("kat")],
+            direction: WordsDirection::TD,
+            guess: String::from("cat"),
+            correct: true,
+            response_ms: 10,
+            timestamp: Utc::now(),
+            session: Utc::now(),
+        };
+        let ser = ron::ser::to_string(&entry).unwrap();
+        // The last line was cut off while it was being written
+        let data = format!("{ser}\nnonsense\n\n{ser}\n{}", &ser[..ser.len() / 2]);
+        let (entries, skipped) = parse(&data);
+        assert_eq!(entries, [entry.clone(), entry]);
+        assert_eq!(skipped, 2);
+    }
+}
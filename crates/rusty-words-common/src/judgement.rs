@@ -1,9 +1,14 @@
 // TODO: Make this more advanced
-use clap::ValueEnum;
+use clap::{Args, ValueEnum};
 use lazy_regex::regex_replace_all;
-use std::borrow::Borrow;
+use serde::{Deserialize, Serialize};
+use std::{borrow::Borrow, fmt::Display};
+use strsim::damerau_levenshtein;
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
 
-#[derive(ValueEnum, Debug, Clone)]
+use crate::symbol_table::fold_symbol;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TryMethod {
     /// Literally type the definition
     Write,
@@ -11,154 +16,558 @@ pub enum TryMethod {
     Mpc,
 }
 
-pub fn check_word<'a, S: Borrow<str>>(method: &TryMethod, input: &'a str, check: &'a [S]) -> bool {
-    !check.is_empty()
-        && (check_word_(method, input, check) || check_word_(method, input, &[check.join(", ")]))
+impl Display for TryMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TryMethod::Write => write!(f, "writing"),
+            TryMethod::Mpc => write!(f, "multiple choice"),
+        }
+    }
+}
+
+/// How the user is judged while practicing. Can be set globally in the config file, per list, and
+/// be overridden for a single session.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JudgementConfig {
+    /// How many correct answers are needed before a word is learned
+    pub correct_needed: usize,
+    /// How many words to keep in rotation
+    pub rotation: usize,
+    /// Whether a wrong answer resets the progress made on a word
+    pub reset_on_mistake: bool,
+    /// When practicing both ways, how to choose the direction a word is asked in
+    pub both_split: BothSplit,
+    /// How strictly written answers are checked
+    pub strictness: Strictness,
+    /// The maximum amount of typos accepted in a written answer, see [typo_threshold]. Ignored when
+    /// the strictness is [Strictness::Exact].
+    pub max_typos: usize,
+}
+
+impl Default for JudgementConfig {
+    fn default() -> Self {
+        Self {
+            correct_needed: 3,
+            rotation: 10,
+            reset_on_mistake: false,
+            both_split: BothSplit::Half,
+            strictness: Strictness::Normal,
+            max_typos: 2,
+        }
+    }
+}
+
+impl Display for JudgementConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "correct_needed={} rotation={} reset_on_mistake={} both_split={:?} strictness={:?} max_typos={}",
+            self.correct_needed,
+            self.rotation,
+            self.reset_on_mistake,
+            self.both_split,
+            self.strictness,
+            self.max_typos
+        )
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BothSplit {
+    /// Ask term -> definition for the first half of the progress, then definition -> term
+    Half,
+    /// Alternate between term -> definition and definition -> term
+    Alternate,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Strictness {
+    /// Answers have to match exactly, except for surrounding whitespace. Typos are never accepted.
+    Exact,
+    /// Ignore case and look-alike symbols, and allow leaving out parts between parentheses
+    Normal,
+    /// Like normal, but also accept answers with wrong or missing diacritics (with a warning)
+    Lenient,
+}
+
+/// Overrides for a [JudgementConfig], given on the command line
+#[derive(Args, Debug, Clone, Default)]
+pub struct JudgementArgs {
+    /// How many correct answers are needed before a word is learned
+    #[clap(long)]
+    pub correct_needed: Option<usize>,
+    /// How many words to keep in rotation
+    #[clap(long)]
+    pub rotation: Option<usize>,
+    /// Whether a wrong answer resets the progress made on a word
+    #[clap(long)]
+    pub reset_on_mistake: Option<bool>,
+    #[clap(value_enum, long)]
+    pub both_split: Option<BothSplit>,
+    #[clap(value_enum, long)]
+    pub strictness: Option<Strictness>,
+    /// The maximum amount of typos accepted in a written answer, 0 to disable
+    #[clap(long)]
+    pub max_typos: Option<usize>,
+}
+
+impl JudgementArgs {
+    /// Whether none of the overrides are given
+    pub fn is_empty(&self) -> bool {
+        self.correct_needed.is_none()
+            && self.rotation.is_none()
+            && self.reset_on_mistake.is_none()
+            && self.both_split.is_none()
+            && self.strictness.is_none()
+            && self.max_typos.is_none()
+    }
+
+    pub fn apply_to(&self, config: &mut JudgementConfig) {
+        if let Some(x) = self.correct_needed {
+            config.correct_needed = x.max(1);
+        }
+        if let Some(x) = self.rotation {
+            config.rotation = x.max(1);
+        }
+        if let Some(x) = self.reset_on_mistake {
+            config.reset_on_mistake = x;
+        }
+        if let Some(x) = self.both_split {
+            config.both_split = x;
+        }
+        if let Some(x) = self.strictness {
+            config.strictness = x;
+        }
+        if let Some(x) = self.max_typos {
+            config.max_typos = x;
+        }
+    }
+}
+
+/// The result of checking an answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Judgement {
+    Exact,
+    /// Only correct when ignoring diacritics, see [Strictness::Lenient]
+    AcceptedWithoutDiacritics,
+    /// Close enough to `expected`, which is `distance` edits away from the answer
+    AcceptedWithTypo {
+        expected: String,
+        distance: usize,
+    },
+    Wrong,
+}
+
+impl Judgement {
+    pub fn is_correct(&self) -> bool {
+        !matches!(self, Judgement::Wrong)
+    }
+
+    /// Whether the word was known without help. A correct answer which needed `hints` is not
+    /// fully correct: it is recorded as wrong on the entry (see
+    /// [crate::model::WordsEntry::answered]), it doesn't count towards learning the word in a
+    /// session but doesn't reset its progress either, and a review gives it
+    /// [crate::scheduler::QUALITY_HINTED].
+    pub fn is_known(&self, hints: usize) -> bool {
+        self.is_correct() && hints == 0
+    }
 }
 
-fn check_word_<'a, S: Borrow<str>>(method: &TryMethod, input: &'a str, check: &'a [S]) -> bool {
-    check.iter().any(|x| match method {
+/// How many typos are accepted in an answer of `len` characters: none for short answers, one per
+/// 5 characters, but never more than `max_typos`.
+pub fn typo_threshold(len: usize, max_typos: usize) -> usize {
+    (len / 5).min(max_typos)
+}
+
+/// Checks the answer with the default strictness, without accepting typos
+pub fn check_word<'a, S: Borrow<str>>(
+    method: &TryMethod,
+    input: &'a str,
+    check: &'a [S],
+) -> Judgement {
+    let config = JudgementConfig {
+        max_typos: 0,
+        ..Default::default()
+    };
+    check_word_with(method, &config, input, check)
+}
+
+pub fn check_word_with<'a, S: Borrow<str>>(
+    method: &TryMethod,
+    config: &JudgementConfig,
+    input: &'a str,
+    check: &'a [S],
+) -> Judgement {
+    if check.is_empty() {
+        return Judgement::Wrong;
+    }
+    let joined = check.join(", ");
+    let candidates: Vec<&str> = check
+        .iter()
+        .map(|x| x.borrow())
+        .chain([joined.as_str()])
+        .collect();
+
+    match method {
+        TryMethod::Mpc if candidates.contains(&input) => Judgement::Exact,
+        TryMethod::Mpc => Judgement::Wrong,
         TryMethod::Write => {
-            let input = input.trim();
-            let x = &x.borrow().trim();
-            let y = regex_replace_all!(r#"\(.*\)"#, &x, "");
-            let y = y.trim();
-            let z = x.replace(['(', ')', ' '], "");
-            let z = z.trim();
-            input.eq_ignore_ascii_case(x)
-                || input.eq_ignore_ascii_case(y)
-                || input.eq_ignore_ascii_case(z)
+            let matches = |strictness| {
+                let input = normalise(input, strictness);
+                candidates
+                    .iter()
+                    .any(|x| accepted_forms(x, strictness).contains(&input))
+            };
+            let strictness = match config.strictness {
+                Strictness::Lenient => Strictness::Normal,
+                e => e,
+            };
+            if matches(strictness) {
+                return Judgement::Exact;
+            }
+            match config.strictness {
+                Strictness::Exact => return Judgement::Wrong,
+                Strictness::Lenient if matches(Strictness::Lenient) => {
+                    return Judgement::AcceptedWithoutDiacritics;
+                }
+                _ => (),
+            }
+
+            let input = normalise(input, config.strictness);
+            candidates
+                .iter()
+                .filter_map(|x| {
+                    accepted_forms(x, config.strictness)
+                        .iter()
+                        .map(|form| {
+                            let threshold = typo_threshold(form.chars().count(), config.max_typos);
+                            (damerau_levenshtein(&input, form), threshold)
+                        })
+                        .filter(|(distance, threshold)| distance <= threshold)
+                        .map(|(distance, _)| distance)
+                        .min()
+                        .map(|distance| (distance, x))
+                })
+                .min_by_key(|(distance, _)| *distance)
+                .map_or(Judgement::Wrong, |(distance, x)| {
+                    Judgement::AcceptedWithTypo {
+                        expected: x.trim().to_string(),
+                        distance,
+                    }
+                })
         }
-        TryMethod::Mpc => input == x.borrow(),
-    })
+    }
+}
+
+/// Hides the letters of `answer` which have not been revealed by `hints` hints with underscores.
+/// The first hint reveals the first letter of every word, every next hint reveals the next hidden
+/// letter. Everything which isn't a letter or digit is always shown.
+pub fn hint(answer: &str, hints: usize) -> String {
+    let mut revealed = hints.saturating_sub(1);
+    let mut start_of_word = true;
+    answer
+        .chars()
+        .map(|x| {
+            let letter = x.is_alphanumeric();
+            let first = std::mem::replace(&mut start_of_word, !letter);
+            match (letter, first && hints > 0) {
+                (false, _) | (true, true) => x,
+                (true, false) if revealed > 0 => {
+                    revealed -= 1;
+                    x
+                }
+                (true, false) => '_',
+            }
+        })
+        .collect()
+}
+
+/// How many hints it takes to reveal all of `answer`, see [hint]
+pub fn max_hints(answer: &str) -> usize {
+    let letters = answer.chars().filter(|x| x.is_alphanumeric()).count();
+    let first = hint(answer, 1);
+    1 + letters - first.chars().filter(|x| x.is_alphanumeric()).count()
+}
+
+/// The normalised forms of an answer which are accepted: the answer itself, and unless the
+/// strictness is [Strictness::Exact], the answer without the part between parentheses and without
+/// the parentheses and spaces.
+fn accepted_forms(x: &str, strictness: Strictness) -> Vec<String> {
+    let x = normalise(x, strictness);
+    if strictness == Strictness::Exact {
+        return vec![x];
+    }
+    let y = regex_replace_all!(r#"\(.*\)"#, &x, "").trim().to_string();
+    let z = x.replace(['(', ')', ' '], "").trim().to_string();
+    vec![x, y, z]
+}
+
+/// Normalises a written answer so it can be compared:
+/// - [Strictness::Exact]: NFC, so composed and decomposed characters are equal
+/// - [Strictness::Normal]: NFKC, case folding and folding symbols into their ASCII look-alikes
+/// - [Strictness::Lenient]: all of the above, and diacritics are stripped
+pub fn normalise(s: &str, strictness: Strictness) -> String {
+    let s = s.trim();
+    match strictness {
+        Strictness::Exact => s.nfc().collect(),
+        Strictness::Normal => caseless::default_case_fold_str(&s.nfkc().collect::<String>())
+            .nfc()
+            .filter_map(fold_symbol)
+            .collect(),
+        Strictness::Lenient => normalise(s, Strictness::Normal)
+            .nfd()
+            .filter(|&c| !is_combining_mark(c))
+            .nfc()
+            .collect(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_hint() {
+        assert_eq!(hint("the big dog", 0), "___ ___ ___");
+        assert_eq!(hint("the big dog", 1), "t__ b__ d__");
+        assert_eq!(hint("the big dog", 3), "the b__ d__");
+        assert_eq!(hint("l'eau (f)", 1), "l'e__ (f)");
+        assert_eq!(max_hints("the big dog"), 7);
+        assert_eq!(hint("the big dog", 7), "the big dog");
+        assert_eq!(max_hints(""), 1);
+    }
+
+    /// A config which only checks the strictness, without accepting typos
+    fn config(strictness: Strictness) -> JudgementConfig {
+        JudgementConfig {
+            strictness,
+            max_typos: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_known() {
+        assert!(Judgement::Exact.is_known(0));
+        assert!(!Judgement::Exact.is_known(2));
+        assert!(!Judgement::Wrong.is_known(0));
+    }
+
     #[test]
     fn test_empty() {
-        assert!(!check_word::<&str>(&TryMethod::Write, "", &[]));
-        assert!(check_word(&TryMethod::Write, "", &[""]));
-        assert!(!check_word(&TryMethod::Write, "", &["foo"]));
-        assert!(!check_word(&TryMethod::Write, "", &["foo", "bar", "baz"]));
+        assert!(!check_word::<&str>(&TryMethod::Write, "", &[]).is_correct());
+        assert!(check_word(&TryMethod::Write, "", &[""]).is_correct());
+        assert!(!check_word(&TryMethod::Write, "", &["foo"]).is_correct());
+        assert!(!check_word(&TryMethod::Write, "", &["foo", "bar", "baz"]).is_correct());
 
-        assert!(!check_word::<&str>(&TryMethod::Mpc, "", &[]));
-        assert!(check_word(&TryMethod::Mpc, "", &[""]));
-        assert!(!check_word(&TryMethod::Mpc, "", &["foo"]));
-        assert!(!check_word(&TryMethod::Mpc, "", &["foo", "bar", "baz"]));
+        assert!(!check_word::<&str>(&TryMethod::Mpc, "", &[]).is_correct());
+        assert!(check_word(&TryMethod::Mpc, "", &[""]).is_correct());
+        assert!(!check_word(&TryMethod::Mpc, "", &["foo"]).is_correct());
+        assert!(!check_word(&TryMethod::Mpc, "", &["foo", "bar", "baz"]).is_correct());
     }
 
     #[test]
     fn test_single() {
-        assert!(check_word(&TryMethod::Write, "foo", &["foo"]));
-        assert!(check_word(&TryMethod::Write, "bar", &["bar"]));
-        assert!(!check_word(&TryMethod::Write, "barz", &["bar"]));
+        assert!(check_word(&TryMethod::Write, "foo", &["foo"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "bar", &["bar"]).is_correct());
+        assert!(!check_word(&TryMethod::Write, "barz", &["bar"]).is_correct());
 
-        assert!(check_word(&TryMethod::Mpc, "foo", &["foo"]));
-        assert!(check_word(&TryMethod::Mpc, "bar", &["bar"]));
-        assert!(!check_word(&TryMethod::Mpc, "barz", &["bar"]));
+        assert!(check_word(&TryMethod::Mpc, "foo", &["foo"]).is_correct());
+        assert!(check_word(&TryMethod::Mpc, "bar", &["bar"]).is_correct());
+        assert!(!check_word(&TryMethod::Mpc, "barz", &["bar"]).is_correct());
     }
 
     #[test]
     fn test_one_of_multiple() {
-        assert!(check_word(&TryMethod::Write, "foo", &["baz", "foo"]));
-        assert!(check_word(&TryMethod::Write, "bar", &["baz", "bar", "baz"]));
-        assert!(!check_word(
-            &TryMethod::Write,
-            "barz",
-            &["foo", "baz", "bar"]
-        ));
+        assert!(check_word(&TryMethod::Write, "foo", &["baz", "foo"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "bar", &["baz", "bar", "baz"]).is_correct());
+        assert!(!check_word(&TryMethod::Write, "barz", &["foo", "baz", "bar"]).is_correct());
 
-        assert!(check_word(&TryMethod::Mpc, "foo", &["baz", "foo"]));
-        assert!(check_word(&TryMethod::Mpc, "bar", &["baz", "bar", "baz"]));
-        assert!(!check_word(&TryMethod::Mpc, "barz", &["foo", "baz", "bar"]));
+        assert!(check_word(&TryMethod::Mpc, "foo", &["baz", "foo"]).is_correct());
+        assert!(check_word(&TryMethod::Mpc, "bar", &["baz", "bar", "baz"]).is_correct());
+        assert!(!check_word(&TryMethod::Mpc, "barz", &["foo", "baz", "bar"]).is_correct());
     }
 
     #[test]
     fn test_all_of_multiple() {
-        assert!(check_word(&TryMethod::Write, "baz, foo", &["baz", "foo"]));
-        assert!(check_word(&TryMethod::Write, "baz,foo", &["baz", "foo"]));
-        assert!(check_word(
-            &TryMethod::Write,
-            "baz, bar, baz",
-            &["baz", "bar", "baz"]
-        ));
-
-        assert!(check_word(&TryMethod::Mpc, "baz, foo", &["baz", "foo"]));
-        assert!(check_word(
-            &TryMethod::Mpc,
-            "baz, bar, baz",
-            &["baz", "bar", "baz"]
-        ));
+        assert!(check_word(&TryMethod::Write, "baz, foo", &["baz", "foo"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "baz,foo", &["baz", "foo"]).is_correct());
+        assert!(
+            check_word(&TryMethod::Write, "baz, bar, baz", &["baz", "bar", "baz"]).is_correct()
+        );
+
+        assert!(check_word(&TryMethod::Mpc, "baz, foo", &["baz", "foo"]).is_correct());
+        assert!(check_word(&TryMethod::Mpc, "baz, bar, baz", &["baz", "bar", "baz"]).is_correct());
         // In multiple choice mode, only match exactly
-        assert!(!check_word(
-            &TryMethod::Mpc,
-            "baz,bar,baz",
-            &["baz", "bar", "baz"]
-        ));
-        assert!(!check_word(&TryMethod::Mpc, "barz", &["foo", "baz", "bar"]));
+        assert!(!check_word(&TryMethod::Mpc, "baz,bar,baz", &["baz", "bar", "baz"]).is_correct());
+        assert!(!check_word(&TryMethod::Mpc, "barz", &["foo", "baz", "bar"]).is_correct());
     }
 
     #[test]
     fn test_mixed_case() {
-        assert!(check_word(&TryMethod::Write, "fOo", &["foo"]));
-        assert!(check_word(&TryMethod::Write, "foo", &["FOO"]));
-        assert!(check_word(&TryMethod::Write, "baR", &["bar"]));
-        assert!(!check_word(&TryMethod::Write, "BARZ", &["bar"]));
+        assert!(check_word(&TryMethod::Write, "fOo", &["foo"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "foo", &["FOO"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "baR", &["bar"]).is_correct());
+        assert!(!check_word(&TryMethod::Write, "BARZ", &["bar"]).is_correct());
 
         // In multiple choice mode, only match exactly
-        assert!(!check_word(&TryMethod::Mpc, "Foo", &["foo"]));
-        assert!(!check_word(&TryMethod::Mpc, "Bar", &["bar"]));
+        assert!(!check_word(&TryMethod::Mpc, "Foo", &["foo"]).is_correct());
+        assert!(!check_word(&TryMethod::Mpc, "Bar", &["bar"]).is_correct());
     }
 
     #[test]
     fn test_sentence() {
-        assert!(check_word(
-            &TryMethod::Write,
-            "the quick brown fox, jumped over the lazy dog.",
-            &["The quick brown fox, jumped over the lazy dog."]
-        ));
+        assert!(
+            check_word(
+                &TryMethod::Write,
+                "the quick brown fox, jumped over the lazy dog.",
+                &["The quick brown fox, jumped over the lazy dog."]
+            )
+            .is_correct()
+        );
 
-        assert!(!check_word(
-            &TryMethod::Mpc,
-            "the quick brown fox, jumped over the lazy dog.",
-            &["The quick brown fox, jumped over the lazy dog."]
-        ));
+        assert!(
+            !check_word(
+                &TryMethod::Mpc,
+                "the quick brown fox, jumped over the lazy dog.",
+                &["The quick brown fox, jumped over the lazy dog."]
+            )
+            .is_correct()
+        );
     }
 
     #[test]
     fn test_parens() {
-        assert!(check_word(
-            &TryMethod::Write,
-            "Such (optional)",
-            &["Such (optional)"]
-        ));
-        assert!(!check_word(
-            &TryMethod::Write,
-            "Such (optional)",
-            &["Such optional"]
-        ));
-        assert!(check_word(&TryMethod::Write, "Such", &["Such (optional)"]));
-
-        assert!(check_word(
-            &TryMethod::Mpc,
-            "Such (optional)",
-            &["Such (optional)"]
-        ));
-        assert!(!check_word(
-            &TryMethod::Mpc,
-            "Such (optional)",
-            &["Such optional"]
-        ));
-        assert!(!check_word(&TryMethod::Mpc, "Such", &["Such (optional)"]));
+        assert!(
+            check_word(&TryMethod::Write, "Such (optional)", &["Such (optional)"]).is_correct()
+        );
+        assert!(!check_word(&TryMethod::Write, "Such (optional)", &["Such optional"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "Such", &["Such (optional)"]).is_correct());
+
+        assert!(check_word(&TryMethod::Mpc, "Such (optional)", &["Such (optional)"]).is_correct());
+        assert!(!check_word(&TryMethod::Mpc, "Such (optional)", &["Such optional"]).is_correct());
+        assert!(!check_word(&TryMethod::Mpc, "Such", &["Such (optional)"]).is_correct());
+    }
+
+    #[test]
+    fn test_exact() {
+        let exact = &config(Strictness::Exact);
+        assert!(check_word_with(&TryMethod::Write, exact, " foo ", &["foo"]).is_correct());
+        assert!(
+            check_word_with(&TryMethod::Write, exact, "foo, bar", &["foo", "bar"]).is_correct()
+        );
+        assert!(!check_word_with(&TryMethod::Write, exact, "Foo", &["foo"]).is_correct());
+        assert!(
+            !check_word_with(&TryMethod::Write, exact, "Such", &["Such (optional)"]).is_correct()
+        );
+        // Typos are not accepted, even when `max_typos` allows them
+        assert_eq!(
+            check_word_with(&TryMethod::Write, exact, "hous", &["house"]),
+            Judgement::Wrong
+        );
+    }
+
+    #[test]
+    fn test_unicode_case() {
+        assert!(check_word(&TryMethod::Write, "ÉCOLE", &["école"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "STRASSE", &["Straße"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "ΣΟΦΟΣ", &["σοφος"]).is_correct());
+        assert!(!check_word(&TryMethod::Mpc, "ÉCOLE", &["école"]).is_correct());
+    }
+
+    #[test]
+    fn test_composition() {
+        let decomposed = "e\u{301}cole";
+        assert!(check_word(&TryMethod::Write, decomposed, &["école"]).is_correct());
+        assert!(
+            check_word_with(
+                &TryMethod::Write,
+                &config(Strictness::Exact),
+                decomposed,
+                &["école"]
+            )
+            .is_correct()
+        );
+        assert!(check_word(&TryMethod::Write, "ﬁlm", &["film"]).is_correct());
+    }
+
+    #[test]
+    fn test_symbols() {
+        assert!(check_word(&TryMethod::Write, "l'école", &["l’école"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "“quoted”", &["\"quoted\""]).is_correct());
+        assert!(check_word(&TryMethod::Write, "well-known", &["well–known"]).is_correct());
+        assert!(check_word(&TryMethod::Write, "soft\u{AD}ware", &["software"]).is_correct());
+        assert!(!check_word(&TryMethod::Write, "l'ecole", &["l’école"]).is_correct());
+    }
+
+    #[test]
+    fn test_diacritics() {
+        let lenient = &config(Strictness::Lenient);
+        assert!(!check_word(&TryMethod::Write, "ecole", &["école"]).is_correct());
+        assert!(check_word_with(&TryMethod::Write, lenient, "ecole", &["école"]).is_correct());
+        assert!(check_word_with(&TryMethod::Write, lenient, "ÈCOLE", &["école"]).is_correct());
+        assert!(check_word_with(&TryMethod::Write, lenient, "uber", &["über (over)"]).is_correct());
+        assert!(!check_word_with(&TryMethod::Write, lenient, "ecale", &["école"]).is_correct());
+        assert!(!check_word_with(&TryMethod::Mpc, lenient, "ecole", &["école"]).is_correct());
+    }
+
+    #[test]
+    fn test_judgement() {
+        let normal = &config(Strictness::Normal);
+        let lenient = &config(Strictness::Lenient);
+        let check = |config, input| check_word_with(&TryMethod::Write, config, input, &["école"]);
+        assert_eq!(check(normal, "école"), Judgement::Exact);
+        assert_eq!(check(lenient, "école"), Judgement::Exact);
+        assert_eq!(
+            check(lenient, "ecole"),
+            Judgement::AcceptedWithoutDiacritics
+        );
+        assert_eq!(check(lenient, "house"), Judgement::Wrong);
+    }
+
+    #[test]
+    fn test_typos() {
+        let normal = &JudgementConfig::default();
+        let check =
+            |input, check: &[&str]| check_word_with(&TryMethod::Write, normal, input, check);
+        let typo = |expected: &str, distance| Judgement::AcceptedWithTypo {
+            expected: expected.to_string(),
+            distance,
+        };
+        // Short words have to be exact
+        assert_eq!(check("barz", &["bar"]), Judgement::Wrong);
+        assert_eq!(check("hous", &["house"]), typo("house", 1));
+        // Transpositions count as one typo
+        assert_eq!(check("hosue", &["house"]), typo("house", 1));
+        assert_eq!(check("huose", &["dog", "house"]), typo("house", 1));
+        assert_eq!(check("hse", &["house"]), Judgement::Wrong);
+        assert_eq!(check("neighbuor", &["neighbour"]), typo("neighbour", 1));
+        assert_eq!(check("nieghbur", &["neighbour"]), Judgement::Wrong);
+        assert_eq!(
+            check("nieghbuors", &["neighbours (plural)"]),
+            typo("neighbours (plural)", 2)
+        );
+        assert_eq!(check("HOUSE", &["house"]), Judgement::Exact);
+
+        let no_typos = &JudgementConfig {
+            max_typos: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            check_word_with(&TryMethod::Write, no_typos, "hous", &["house"]),
+            Judgement::Wrong
+        );
+        assert_eq!(
+            check_word_with(&TryMethod::Mpc, normal, "hous", &["house"]),
+            Judgement::Wrong
+        );
     }
 
     #[test]
     fn test_trailing_spaces() {
-        assert!(check_word(&TryMethod::Write, "  foo bar  ", &["foo bar"]));
+        assert!(check_word(&TryMethod::Write, "  foo bar  ", &["foo bar"]).is_correct());
 
-        assert!(!check_word(&TryMethod::Mpc, "  foo bar  ", &["foo bar"]));
+        assert!(!check_word(&TryMethod::Mpc, "  foo bar  ", &["foo bar"]).is_correct());
     }
 }
@@ -1,150 +1,267 @@
-pub const LANG_SEARCHES: &[&str] = &[
-    "ab", "aa", "af", "sq", "am", "ar", "hy", "as", "ay", "az", "ba", "eu", "bn", "dz", "bh", "bi",
-    "br", "bg", "my", "be", "km", "ca", "zh", "co", "hr", "cs", "da", "nl", "en", "eo", "et", "fo",
-    "fj", "fi", "fr", "fy", "gd", "gl", "ka", "de", "el", "kl", "gn", "gu", "ha", "iw", "hi", "hu",
-    "is", "in", "ia", "ie", "ik", "ga", "it", "ja", "jw", "kn", "ks", "kk", "rw", "ky", "rn", "ko",
-    "ku", "lo", "la", "lv", "ln", "lt", "mk", "mg", "ms", "ml", "mt", "mi", "mr", "mo", "mn", "na",
-    "ne", "no", "oc", "or", "om", "ps", "fa", "pl", "pt", "pa", "qu", "rm", "ro", "ru", "sm", "sg",
-    "sa", "sr", "sh", "st", "tn", "sn", "sd", "si", "ss", "sk", "sl", "so", "es", "su", "sw", "sv",
-    "tl", "tg", "ta", "tt", "te", "th", "bo", "ti", "to", "ts", "tr", "tk", "tw", "uk", "ur", "uz",
-    "vi", "vo", "cy", "wo", "xh", "ji", "yo", "zu",
+//! Languages, as a subset of BCP-47 tags: an ISO 639 language, optionally followed by an ISO 15924
+//! script and an ISO 3166 or UN M.49 region, like `nl`, `en-GB`, `sr-Latn-RS` or `es-419`.
+//!
+//! The language can be given as an ISO 639-1, 639-2 or 639-3 code or by its English name.
+//! Deprecated codes are replaced by their current code.
+
+use std::{fmt::Display, str::FromStr};
+
+use color_eyre::{Report, eyre::eyre};
+
+/// ISO 639-1 codes which have been replaced
+const DEPRECATED: &[(&str, &str)] = &[
+    ("in", "id"),
+    ("iw", "he"),
+    ("ji", "yi"),
+    ("jw", "jv"),
+    ("mo", "ro"),
+    ("sh", "sr"),
 ];
 
-pub const LANG_REPLACES: &[&str] = &[
-    "Abkhazian",
-    "Afar",
-    "Afrikaans",
-    "Albanian",
-    "Amharic",
-    "Arabic",
-    "Armenian",
-    "Assamese",
-    "Aymara",
-    "Azerbaijani",
-    "Bashkir",
-    "Basque",
-    "Bengali,",
-    "Bhutani",
-    "Bihari",
-    "Bislama",
-    "Breton",
-    "Bulgarian",
-    "Burmese",
-    "Byelorussian",
-    "Cambodian",
-    "Catalan",
-    "Chinese",
-    "Corsican",
-    "Croatian",
-    "Czech",
-    "Danish",
-    "Dutch",
-    "English,",
-    "Esperanto",
-    "Estonian",
-    "Faeroese",
-    "Fiji",
-    "Finnish",
-    "French",
-    "Frisian",
-    "Gaelic",
-    "Galician",
-    "Georgian",
-    "German",
-    "Greek",
-    "Greenlandic",
-    "Guarani",
-    "Gujarati",
-    "Hausa",
-    "Hebrew",
-    "Hindi",
-    "Hungarian",
-    "Icelandic",
-    "Indonesian",
-    "Interlingua",
-    "Interlingue",
-    "Inupiak",
-    "Irish",
-    "Italian",
-    "Japanese",
-    "Javanese",
-    "Kannada",
-    "Kashmiri",
-    "Kazakh",
-    "Kinyarwanda",
-    "Kirghiz",
-    "Kirundi",
-    "Korean",
-    "Kurdish",
-    "Laothian",
-    "Latin",
-    "Latvian,",
-    "Lingala",
-    "Lithuanian",
-    "Macedonian",
-    "Malagasy",
-    "Malay",
-    "Malayalam",
-    "Maltese",
-    "Maori",
-    "Marathi",
-    "Moldavian",
-    "Mongolian",
-    "Nauru",
-    "Nepali",
-    "Norwegian",
-    "Occitan",
-    "Oriya",
-    "Oromo,",
-    "Pashto,",
-    "Persian",
-    "Polish",
-    "Portuguese",
-    "Punjabi",
-    "Quechua",
-    "Rhaeto-Romance",
-    "Romanian",
-    "Russian",
-    "Samoan",
-    "Sangro",
-    "Sanskrit",
-    "Serbian",
-    "Serbo-Croatian",
-    "Sesotho",
-    "Setswana",
-    "Shona",
-    "Sindhi",
-    "Singhalese",
-    "Siswati",
-    "Slovak",
-    "Slovenian",
-    "Somali",
-    "Spanish",
-    "Sudanese",
-    "Swahili",
-    "Swedish",
-    "Tagalog",
-    "Tajik",
-    "Tamil",
-    "Tatar",
-    "Tegulu",
-    "Thai",
-    "Tibetan",
-    "Tigrinya",
-    "Tonga",
-    "Tsonga",
-    "Turkish",
-    "Turkmen",
-    "Twi",
-    "Ukrainian",
-    "Urdu",
-    "Uzbek",
-    "Vietnamese",
-    "Volapuk",
-    "Welsh",
-    "Wolof",
-    "Xhosa",
-    "Yiddish",
-    "Yoruba",
-    "Zulu",
+/// ISO 639-2/B codes which differ from the ISO 639-2/T and 639-3 codes
+const BIBLIOGRAPHIC: &[(&str, &str)] = &[
+    ("alb", "sqi"),
+    ("arm", "hye"),
+    ("baq", "eus"),
+    ("bur", "mya"),
+    ("chi", "zho"),
+    ("cze", "ces"),
+    ("dut", "nld"),
+    ("fre", "fra"),
+    ("geo", "kat"),
+    ("ger", "deu"),
+    ("gre", "ell"),
+    ("ice", "isl"),
+    ("mac", "mkd"),
+    ("mao", "mri"),
+    ("may", "msa"),
+    ("per", "fas"),
+    ("rum", "ron"),
+    ("slo", "slk"),
+    ("tib", "bod"),
+    ("wel", "cym"),
 ];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LanguageTag {
+    pub language: isolang::Language,
+    /// Four letters, like `Latn`
+    pub script: Option<[u8; 4]>,
+    /// Two letters or three digits, like `GB` or `419`
+    pub region: Option<Region>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Region {
+    Alpha([u8; 2]),
+    Numeric(u16),
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Alpha(x) => write!(f, "{}", String::from_utf8_lossy(x)),
+            Self::Numeric(x) => write!(f, "{x:03}"),
+        }
+    }
+}
+
+impl LanguageTag {
+    /// The shortest code of the language, ISO 639-1 if it has one
+    pub fn code(&self) -> &'static str {
+        self.language
+            .to_639_1()
+            .unwrap_or_else(|| self.language.to_639_3())
+    }
+
+    /// The English name of the language, followed by the script and region if they are set
+    pub fn name(&self) -> String {
+        let extra = self
+            .script
+            .map(|x| String::from_utf8_lossy(&x).into_owned())
+            .into_iter()
+            .chain(self.region.map(|x| x.to_string()))
+            .collect::<Vec<_>>();
+        match extra.is_empty() {
+            true => self.language.to_name().to_string(),
+            false => format!("{} ({})", self.language.to_name(), extra.join(", ")),
+        }
+    }
+
+    /// Whether `other` is this language, `other` only needs to match the script and region if
+    /// this tag specifies them
+    pub fn includes(&self, other: &Self) -> bool {
+        self.language == other.language
+            && self.script.is_none_or(|x| other.script == Some(x))
+            && self.region.is_none_or(|x| other.region == Some(x))
+    }
+}
+
+impl Display for LanguageTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())?;
+        if let Some(script) = self.script {
+            write!(f, "-{}", String::from_utf8_lossy(&script))?;
+        }
+        if let Some(region) = self.region {
+            write!(f, "-{region}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for LanguageTag {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let mut subtags = s.split(['-', '_']);
+        let Some(language) = from_code(subtags.next().unwrap_or_default()) else {
+            return from_name(s)
+                .map(|language| Self {
+                    language,
+                    script: None,
+                    region: None,
+                })
+                .ok_or_else(|| unknown(s));
+        };
+        let mut tag = Self {
+            language,
+            script: None,
+            region: None,
+        };
+        for subtag in subtags {
+            let alphabetic = subtag.bytes().all(|x| x.is_ascii_alphabetic());
+            match subtag.len() {
+                4 if alphabetic && tag.script.is_none() && tag.region.is_none() => {
+                    let mut script = [0; 4];
+                    for (i, x) in subtag.bytes().enumerate() {
+                        script[i] = match i {
+                            0 => x.to_ascii_uppercase(),
+                            _ => x.to_ascii_lowercase(),
+                        };
+                    }
+                    tag.script = Some(script);
+                }
+                2 if alphabetic && tag.region.is_none() => {
+                    let x = subtag.to_ascii_uppercase().into_bytes();
+                    tag.region = Some(Region::Alpha([x[0], x[1]]));
+                }
+                3 if tag.region.is_none() => {
+                    let x = subtag
+                        .parse()
+                        .map_err(|_| eyre!("Invalid region `{subtag}` in language `{s}`"))?;
+                    tag.region = Some(Region::Numeric(x));
+                }
+                _ => {
+                    return Err(eyre!(
+                        "Invalid language `{s}`, expected a language code optionally followed by a script and a region, like `sr-Latn-RS`"
+                    ));
+                }
+            }
+        }
+        Ok(tag)
+    }
+}
+
+fn from_code(code: &str) -> Option<isolang::Language> {
+    let code = code.to_ascii_lowercase();
+    let replace = |table: &[(&str, &'static str)], code: &str| {
+        table
+            .iter()
+            .find(|(old, _)| *old == code)
+            .map(|(_, new)| *new)
+    };
+    match code.len() {
+        2 => isolang::Language::from_639_1(replace(DEPRECATED, &code).unwrap_or(&code)),
+        3 => isolang::Language::from_639_3(replace(BIBLIOGRAPHIC, &code).unwrap_or(&code)),
+        _ => None,
+    }
+}
+
+fn from_name(name: &str) -> Option<isolang::Language> {
+    let name = name.to_lowercase();
+    isolang::Language::match_names(move |x| x.to_lowercase() == name).next()
+}
+
+fn unknown(language: &str) -> Report {
+    let suggestions = suggestions(language);
+    match suggestions.is_empty() {
+        true => eyre!("Unknown language `{language}`"),
+        false => eyre!(
+            "Unknown language `{language}`, did you mean {}?",
+            suggestions.join(" or ")
+        ),
+    }
+}
+
+/// Up to 3 languages with a name or code similar to `query`, formatted as "name (code)"
+pub fn suggestions(query: &str) -> Vec<String> {
+    let query = query.to_lowercase();
+    let similarity = move |language: isolang::Language| {
+        let name = strsim::jaro_winkler(&query, &language.to_name().to_lowercase());
+        let code = language
+            .to_639_1()
+            .map_or(0.0, |x| strsim::jaro_winkler(&query, x));
+        name.max(code)
+    };
+    // Only the languages with an ISO 639-1 code are common enough to suggest
+    let mut found = isolang::Language::match_names(|_| true)
+        .filter(|x| x.to_639_1().is_some())
+        .map(|x| (similarity(x), x))
+        .filter(|(similarity, _)| *similarity >= 0.85)
+        .collect::<Vec<_>>();
+    found.sort_by(|(x, _), (y, _)| y.total_cmp(x));
+    found
+        .into_iter()
+        .take(3)
+        .map(|(_, x)| format!("{} ({})", x.to_name(), x.to_639_1().unwrap_or_default()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> String {
+        s.parse::<LanguageTag>().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("nl"), "nl");
+        assert_eq!(parse("EN_gb"), "en-GB");
+        assert_eq!(parse("sr-latn-rs"), "sr-Latn-RS");
+        assert_eq!(parse("es-419"), "es-419");
+        assert_eq!(parse("nld"), "nl");
+        assert_eq!(parse("dut"), "nl");
+        assert_eq!(parse("iw"), "he");
+        assert_eq!(parse("French"), "fr");
+        assert_eq!(parse("tlh"), "tlh");
+        assert!("xx".parse::<LanguageTag>().is_err());
+        assert!("en-GB-US".parse::<LanguageTag>().is_err());
+        assert!("en-toolong".parse::<LanguageTag>().is_err());
+    }
+
+    #[test]
+    fn test_name() {
+        let name = |s: &str| s.parse::<LanguageTag>().unwrap().name();
+        assert_eq!(name("nl-BE"), "Dutch (BE)");
+        assert_eq!(name("te"), "Telugu");
+        assert_eq!(name("sr-Latn"), "Serbian (Latn)");
+    }
+
+    #[test]
+    fn test_includes() {
+        let tag = |s: &str| s.parse::<LanguageTag>().unwrap();
+        assert!(tag("fr").includes(&tag("fr-CA")));
+        assert!(!tag("fr-CA").includes(&tag("fr")));
+        assert!(!tag("fr").includes(&tag("en")));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let error = "Frnch".parse::<LanguageTag>().unwrap_err().to_string();
+        assert!(error.contains("French (fr)"), "{error}");
+    }
+}
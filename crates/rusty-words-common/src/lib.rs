@@ -1,5 +1,15 @@
+pub mod config;
+pub mod folders;
+pub mod formats;
+pub mod history;
 pub mod judgement;
 pub mod lang_codes;
 pub mod model;
 pub mod paths;
+pub mod practice;
+pub mod scheduler;
+pub mod search;
+pub mod store;
 pub mod symbol_table;
+#[cfg(test)]
+mod testing;
@@ -5,58 +5,77 @@
 //!   - Name of list
 //!   - Path to words list file (located in ~/.local/share/rusty-words/UUID.ron)
 //!     -> Stores term, definition, times correctly answered for every word.
-//!     -> Tsv files can be imported and exported (losing all data except term and definition)
+//!     -> Tsv, csv and json files can be imported and exported (losing all data except term and
+//!     definition, unless exported with progress)
 //!   - Language for term and definition
 //!   - When created/modified
 //!   - How to check the user if they got a word correct
-//!   - The (fake) folder the list is in
+//!   - The (fake) folder the list is in, see [crate::folders]
 //! - The index file also contains the user's native language (for filtering)
 //!   Ex: The user's native lanuage is "nl" and they search for "en", they want the lists that
 //!   practice nl->en or en->nl.
 //!   Ex: The user's native lanuage is "de" and they search for "en", they are probably not looking
 //!   for en->nl or nl->en, so we sort that later in the list.
+//!
+//! ~/.local/share/rusty-words/index.ron.bak -> The previous index, used if the index is corrupt
 
 use std::{
     borrow::Cow,
-    collections::HashMap,
-    convert::TryFrom,
+    collections::{BTreeSet, HashMap, VecDeque},
+    convert::{Infallible, TryFrom},
     fmt::{Debug, Display},
-    fs::File,
-    io::Write,
     ops::BitAnd,
     path::PathBuf,
     str::FromStr,
 };
 
-use aho_corasick::AhoCorasick;
 use chrono::{DateTime, Utc};
 use clap::{Args, clap_derive::ValueEnum};
 use color_eyre::{
     Help, Report, Result,
     eyre::{Context, eyre},
 };
-use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, serde_as};
 use tabled::{Style, Table, Tabled};
 use uuid::Uuid;
 
-use crate::paths::new_words_file;
+use crate::{
+    folders,
+    formats::{self, Duplicates, Header, ListFormat, ParseArgs},
+    judgement::{JudgementConfig, TryMethod},
+    lang_codes::LanguageTag,
+    paths::{index_file, new_words_file},
+    practice::{Learned, SessionState},
+    scheduler::ReviewState,
+    store,
+};
 
 #[derive(PartialEq, Eq, Clone, Serialize, Deserialize, Default)]
 pub struct WordsIndex {
     pub lists: Vec<WordsMeta>,
+    /// Folders which were created without putting a list in them, see [crate::folders]
+    #[serde(default)]
+    pub folders: BTreeSet<PathBuf>,
+    /// Used to rank the lists when filtering by language
+    #[serde(default)]
+    pub native_language: Language,
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct ImportArgs {
     pub filename: PathBuf,
-    pub term_lang: Option<String>,
-    pub def_lang: Option<String>,
+    pub term_lang: Option<LanguageTag>,
+    pub def_lang: Option<LanguageTag>,
     #[clap(short, long)]
     pub dir: Option<PathBuf>,
     #[clap(value_enum, long)]
     pub direction: Option<WordsDirection>,
+    /// The format of the file, guessed by its extension if not given
+    #[clap(value_enum, short, long)]
+    pub format: Option<ListFormat>,
+    #[clap(flatten)]
+    pub parse: ParseArgs,
 }
 
 impl WordsIndex {
@@ -66,6 +85,84 @@ impl WordsIndex {
     fn _could_not_find(id: usize) -> Report {
         eyre!("Could not find that list by ID {id}")
     }
+
+    /// Loads the index from the store, falling back to the backup made by [WordsIndex::save] if
+    /// the index is corrupt. Returns an empty index if there is no index yet.
+    pub fn load() -> Result<Self> {
+        let path = index_file()?;
+        if !path.exists() || path.metadata()?.len() == 0 {
+            return Ok(Self::default());
+        }
+        let mut index: Self = match store::read_ron(&path) {
+            Ok(index) => index,
+            Err(e) => {
+                let backup = store::backup_file(&path);
+                let Ok(index) = store::read_ron(&backup) else {
+                    return Err(e
+                        .wrap_err("There is an error in the index file which cannot be resolved.")
+                        .with_note(|| {
+                            format!("The backup {} is not usable either.", backup.display())
+                        }));
+                };
+                eprintln!(
+                    "Warning: the index file is corrupt ({e:#}), using the backup {} instead.",
+                    backup.display()
+                );
+                index
+            }
+        };
+        index.migrate();
+        Ok(index)
+    }
+
+    /// Moves what older versions stored to where it is stored now
+    fn migrate(&mut self) {
+        for meta in &mut self.lists {
+            if let Some(learned) = meta.progress.take() {
+                meta.session.get_or_insert(SessionState {
+                    learned: Learned::Count(learned),
+                    ..SessionState::new(None, WordsDirection::Auto, TryMethod::Write)
+                });
+            }
+        }
+    }
+
+    /// Warns about the languages in the index which can't be parsed, see [Language::Unparsed]
+    pub fn warn_unparsed_languages(&self) -> Result<()> {
+        let path = index_file()?;
+        if let Language::Unparsed(ref raw) = self.native_language {
+            eprintln!(
+                "Warning: your native language `{raw}` is not known, so it is ignored. Use \
+                `configure --native-language` to set it again."
+            );
+        }
+        for meta in &self.lists {
+            for language in [&meta.terms, &meta.definition] {
+                if let Language::Unparsed(raw) = language {
+                    eprintln!(
+                        "Warning: the language `{raw}` of list `{}` is not known, so it is \
+                        ignored. Change it to a language code (like `nl`) in {} to use it again.",
+                        meta.name,
+                        path.display()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Atomically saves the index to the store, keeping the previous index as a backup if it was
+    /// valid
+    pub fn save(&self) -> Result<()> {
+        let path = index_file()?;
+        if let Ok(previous) = std::fs::read(&path) {
+            if ron::de::from_bytes::<Self>(&previous).is_ok() {
+                store::write_atomic(&store::backup_file(&path), &previous)?;
+            }
+        }
+        store::write_ron(&path, self)
+    }
+
     pub fn get(&self, id: usize) -> Result<&WordsMeta> {
         let meta = self
             .lists
@@ -79,6 +176,136 @@ impl WordsIndex {
             })?;
         Ok(meta)
     }
+    pub fn get_mut(&mut self, id: usize) -> Result<&mut WordsMeta> {
+        let meta = self
+            .lists
+            .get_mut(
+                id.checked_sub(1)
+                    .ok_or_else(Self::_underflow)
+                    .with_context(|| "While getting a list.")?,
+            )
+            .ok_or_else(|| {
+                Self::_could_not_find(id).with_note(|| "Occured while getting a list.")
+            })?;
+        Ok(meta)
+    }
+    /// Finds the list a handle refers to and returns its ID. Tried in this order, the first one
+    /// which matches anything wins:
+    /// 1. The ID, a handle which is a number is always an ID (so a list named "2" has to be
+    ///    referred to differently)
+    /// 2. The full UUID
+    /// 3. The exact name, first case sensitive and then ignoring case
+    /// 4. A prefix of the UUID or a part of the name, ignoring case
+    /// 5. A name which is similar to the handle, for typos
+    ///
+    /// If more than one list matches, this fails and lists the candidates.
+    pub fn resolve(&self, handle: &ListHandle) -> Result<usize> {
+        self.resolve_with(handle, true)
+    }
+
+    /// Like [Self::resolve], but only accepts the ID, (a unique prefix of) the UUID or the exact
+    /// name. Used by the commands which remove or move lists, so a typo can't hit another list.
+    pub fn resolve_exact(&self, handle: &ListHandle) -> Result<usize> {
+        self.resolve_with(handle, false)
+    }
+
+    fn resolve_with(&self, handle: &ListHandle, fuzzy: bool) -> Result<usize> {
+        let query = handle.0.trim();
+        let lowercase = query.to_lowercase();
+        if let Ok(id) = query.parse::<usize>() {
+            return match (1..=self.lists.len()).contains(&id) {
+                true => Ok(id),
+                false => Err(eyre!("There is no list with ID {id}")
+                    .with_suggestion(|| "Use `ls` to see the names and ID's of all lists.")),
+            };
+        }
+        if let Ok(uuid) = Uuid::parse_str(query) {
+            if let Some(i) = self.lists.iter().position(|x| x.uuid == uuid) {
+                return Ok(i + 1);
+            }
+        }
+
+        let similar = |x: &WordsMeta| {
+            strsim::normalized_damerau_levenshtein(&lowercase, &x.name.to_lowercase()) >= 0.7
+        };
+        let fuzzy_tiers: [&dyn Fn(&WordsMeta) -> bool; 4] = [
+            &|x| x.name == query,
+            &|x| x.name.to_lowercase() == lowercase,
+            &|x| {
+                x.uuid.to_string().starts_with(&lowercase)
+                    || x.name.to_lowercase().contains(&lowercase)
+            },
+            &similar,
+        ];
+        let exact_tiers: [&dyn Fn(&WordsMeta) -> bool; 2] = [&|x| x.name == query, &|x| {
+            x.uuid.to_string().starts_with(&lowercase)
+        }];
+        let tiers = match fuzzy {
+            true => &fuzzy_tiers[..],
+            false => &exact_tiers[..],
+        };
+        for matches in tiers {
+            let found = self
+                .lists
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| matches(x))
+                .map(|(i, _)| i + 1)
+                .collect::<Vec<_>>();
+            match found[..] {
+                [] => continue,
+                [id] => return Ok(id),
+                _ => {
+                    return Err(eyre!("`{handle}` matches more than one list:")
+                        .with_note(|| self.describe(&found))
+                        .with_suggestion(|| "Use the ID or (part of) the UUID instead."));
+                }
+            }
+        }
+        Err(eyre!("Could not find a list matching `{handle}`")
+            .with_suggestion(|| "Use `ls` to see the names and ID's of all lists."))
+    }
+
+    fn describe(&self, ids: &[usize]) -> String {
+        ids.iter()
+            .map(|&id| {
+                let meta = &self.lists[id - 1];
+                format!("{id}. {} ({})", meta.name, meta.uuid)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The ID's of the lists which have `language` as the language of the terms or the
+    /// definitions. The lists which practice it together with the native language come first.
+    pub fn lists_with_language(&self, language: &LanguageTag) -> Vec<usize> {
+        let mut found = self
+            .lists
+            .iter()
+            .enumerate()
+            .filter_map(|(i, x)| {
+                let other = match (x.terms.matches(language), x.definition.matches(language)) {
+                    (true, _) => &x.definition,
+                    (false, true) => &x.terms,
+                    (false, false) => return None,
+                };
+                let native = self
+                    .native_language
+                    .tag()
+                    .is_some_and(|native| other.matches(&native));
+                Some((i + 1, native))
+            })
+            .collect::<Vec<_>>();
+        found.sort_by_key(|(_, native)| !native);
+        found.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Removes the list with this UUID from the index, if it is in there
+    pub fn remove_uuid(&mut self, uuid: &Uuid) -> Option<WordsMeta> {
+        let i = self.lists.iter().position(|x| x.uuid == *uuid)?;
+        Some(self.lists.remove(i))
+    }
+
     pub fn remove(&mut self, id: usize) -> Result<WordsMeta> {
         if id > self.lists.len() {
             return Err(Self::_could_not_find(id).with_note(|| "Occured while deleting a list."));
@@ -89,44 +316,68 @@ impl WordsIndex {
         Ok(meta)
     }
 
-    /// Returns the ID of the new entry
+    /// Returns the ID of the new entry, and the lines which have a term of an earlier line
     pub fn import_list<'a>(
         &mut self,
         name: &'a str,
         data: &'a str,
         args: &ImportArgs,
-    ) -> Result<usize> {
+    ) -> Result<(usize, Vec<DuplicateLine>)> {
         let ImportArgs {
             filename,
             direction,
             term_lang,
             def_lang,
             dir,
+            format,
+            parse,
         } = args;
-        let parsed = PrimitiveWordsList::try_from(data)
+        let format = format.unwrap_or_else(|| ListFormat::from_path(filename));
+        let (mut list, duplicate_lines) = formats::import(data, format, parse)
             .with_context(|| format!("while trying to import {}", filename.display()))?;
 
-        let mut list = WordsList::from(parsed);
         list.apply_direction(*direction);
         let meta = WordsMeta::new(
             name.to_owned(),
-            term_lang.clone(),
-            def_lang.clone(),
-            dir.clone(),
+            *term_lang,
+            *def_lang,
+            dir.as_deref()
+                .map(folders::normalise)
+                .transpose()?
+                .flatten(),
         );
         let words_file = new_words_file(&meta.uuid)?;
+        // Never overwrite the words of another list
+        if words_file.exists() {
+            return Err(eyre!(
+                "Couldn't create the words file, `{}` already exists.",
+                words_file.display()
+            ));
+        }
         self.lists.push(meta);
 
-        let ser = ron::ser::to_string_pretty(&list, PrettyConfig::default())?;
-        write!(
-            &mut File::options()
-                .write(true)
-                .create_new(true)
-                .open(words_file)?,
-            "{ser}"
-        )?;
+        store::write_ron(&words_file, &list)?;
+
+        Ok((self.lists.len(), duplicate_lines))
+    }
+}
 
-        Ok(self.lists.len())
+/// Refers to a list in the index by its ID, (a prefix of) its UUID or (a part of) its name, see
+/// [WordsIndex::resolve]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ListHandle(pub String);
+
+impl FromStr for ListHandle {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl Display for ListHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
@@ -143,43 +394,97 @@ pub struct WordsMeta {
     #[serde_as(as = "DisplayFromStr")]
     pub last_modified: DateTime<Utc>,
     pub folder: Option<PathBuf>,
-    pub progress: Option<usize>,
-    pub shuffle_map: Option<HashMap<usize, usize>>,
+    /// Where `try` was left off, `None` if the next session starts from the beginning
+    #[serde(default)]
+    pub session: Option<SessionState>,
+    /// Overrides the judgement config from the config file for this list
+    #[serde(default)]
+    pub judgement: Option<JudgementConfig>,
+    /// How many entries were learned, as stored by older versions. It is moved into `session` when
+    /// the index is loaded.
+    #[serde(default, skip_serializing)]
+    progress: Option<usize>,
 }
 
-#[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
-pub struct Language(pub Option<String>);
+/// The language of the terms or the definitions of a list, see [crate::lang_codes]
+#[derive(PartialEq, Eq, Clone, Default)]
+pub enum Language {
+    #[default]
+    Unset,
+    Tag(LanguageTag),
+    /// A stored language which can't be parsed (anymore), it is ignored but kept as it is so it
+    /// isn't lost when the index is saved
+    Unparsed(String),
+}
+
+impl Language {
+    /// The language, `None` if it isn't set or can't be parsed
+    pub fn tag(&self) -> Option<LanguageTag> {
+        match self {
+            Self::Tag(tag) => Some(*tag),
+            _ => None,
+        }
+    }
+
+    /// Whether this is the language `query`, see [LanguageTag::includes]
+    pub fn matches(&self, query: &LanguageTag) -> bool {
+        self.tag().is_some_and(|x| query.includes(&x))
+    }
+}
+
+impl From<Option<LanguageTag>> for Language {
+    fn from(tag: Option<LanguageTag>) -> Self {
+        tag.map_or(Self::Unset, Self::Tag)
+    }
+}
+
+impl Serialize for Language {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let raw = match self {
+            Self::Unset => None,
+            Self::Tag(tag) => Some(tag.to_string()),
+            Self::Unparsed(raw) => Some(raw.clone()),
+        };
+        serializer.serialize_newtype_struct("Language", &raw)
+    }
+}
+
+/// Languages which can't be parsed (anymore) become [Language::Unparsed], instead of making the
+/// whole index unreadable
+impl<'de> Deserialize<'de> for Language {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "Language")]
+        struct Raw(Option<String>);
+
+        let Raw(raw) = Raw::deserialize(deserializer)?;
+        Ok(match raw {
+            None => Self::Unset,
+            Some(raw) => raw.parse().map_or(Self::Unparsed(raw), Self::Tag),
+        })
+    }
+}
 
 impl Display for Language {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.0 {
-            Some(ref s) => write!(f, "{}", format_language_code(s)),
-            None => write!(f, "not set"),
+        match self {
+            Self::Unset => write!(f, "not set"),
+            Self::Tag(tag) => write!(f, "{}", tag.name()),
+            Self::Unparsed(raw) => write!(f, "{raw} (unknown)"),
         }
     }
 }
 
 impl Debug for Language {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self.0 {
-                Some(ref s) => &s,
-                None => "null",
-            }
-        )
+        match self {
+            Self::Unset => write!(f, "null"),
+            Self::Tag(tag) => write!(f, "{tag}"),
+            Self::Unparsed(raw) => write!(f, "{raw}"),
+        }
     }
 }
 
-pub fn format_language_code(haystack: impl AsRef<str>) -> String {
-    let ac = AhoCorasick::new(super::lang_codes::LANG_SEARCHES);
-    ac.replace_all(
-        &haystack.as_ref().to_ascii_lowercase(),
-        super::lang_codes::LANG_REPLACES,
-    )
-}
-
 impl Display for WordsMeta {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let WordsMeta {
@@ -190,8 +495,9 @@ impl Display for WordsMeta {
             definition,
             uuid,
             folder,
-            progress,
-            shuffle_map,
+            session,
+            judgement,
+            progress: _,
         } = self;
         if f.alternate() {
             writeln!(
@@ -204,42 +510,59 @@ last_modified	{}
 folder	{}
 uuid	{}
 progress	{}
-shuffle_map	{}"#,
-                last_modified,
+seed	{}
+judgement	{}"#,
                 created_at,
+                last_modified,
                 folder
                     .clone()
                     .unwrap_or_else(|| PathBuf::from("null"))
                     .display(),
                 uuid,
-                progress.unwrap_or(0),
-                shuffle_map
+                session.as_ref().map_or(0, |x| x.learned.count()),
+                session
                     .as_ref()
-                    .map(|x| format!("{x:?}"))
+                    .and_then(|x| x.seed)
+                    .map(|x| x.to_string())
+                    .unwrap_or_else(|| String::from("null")),
+                judgement
+                    .as_ref()
+                    .map(|x| x.to_string())
                     .unwrap_or_else(|| String::from("null")),
             )?;
         } else {
             writeln!(f, "Name: {name}")?;
-            if let Some(ref term) = terms.0 {
-                writeln!(f, "Terms: {}", format_language_code(term))?;
+            if *terms != Language::Unset {
+                writeln!(f, "Terms: {terms}")?;
             }
-            if let Some(ref definition) = definition.0 {
-                writeln!(f, "Definitions: {}", format_language_code(definition))?;
+            if *definition != Language::Unset {
+                writeln!(f, "Definitions: {definition}")?;
             }
             writeln!(
                 f,
                 "Created at: {}\nLast modified at: {}",
-                last_modified, created_at
+                created_at, last_modified
             )?;
             if let Some(ref folder) = folder {
                 writeln!(f, "Folder: {}", folder.display())?;
             }
             writeln!(f, "UUID: {uuid}")?;
-            if let Some(progress) = progress {
-                writeln!(f, "Progress: {progress}")?;
+            if let Some(session) = session {
+                writeln!(
+                    f,
+                    "Progress: {} learned, {} in rotation ({}, {}{})",
+                    session.learned.count(),
+                    session.rotation.len(),
+                    session.method,
+                    session.direction,
+                    match session.seed {
+                        Some(seed) => format!(", shuffled with seed {seed}"),
+                        None => String::new(),
+                    }
+                )?;
             }
-            if let Some(shuffle) = shuffle_map {
-                writeln!(f, "Shuffle: {shuffle:#?}")?;
+            if let Some(judgement) = judgement {
+                writeln!(f, "Judgement: {judgement}")?;
             }
         }
         Ok(())
@@ -249,8 +572,8 @@ shuffle_map	{}"#,
 impl WordsMeta {
     pub fn new(
         name: String,
-        terms: Option<String>,
-        definition: Option<String>,
+        terms: Option<LanguageTag>,
+        definition: Option<LanguageTag>,
         folder: Option<PathBuf>,
     ) -> Self {
         let uuid = uuid::Builder::from_random_bytes(rand::random()).into_uuid();
@@ -258,22 +581,32 @@ impl WordsMeta {
 
         Self {
             name,
-            terms: Language(terms),
-            definition: Language(definition),
+            terms: terms.into(),
+            definition: definition.into(),
             folder,
             uuid,
             created_at,
             last_modified: created_at,
+            session: None,
+            judgement: None,
             progress: None,
-            shuffle_map: None,
         }
     }
+
+    /// The start of the UUID, which is shown as a short handle for the list. It includes the
+    /// separator if the first 8 characters are digits, so it can't be mistaken for an ID.
+    pub fn short_uuid(&self) -> String {
+        let uuid = self.uuid.to_string();
+        let len = match uuid[..8].bytes().all(|x| x.is_ascii_digit()) {
+            true => 9,
+            false => 8,
+        };
+        uuid[..len].to_string()
+    }
 }
 
+/// The entries of a list, where `try` was left off is stored in [WordsMeta::session]
 #[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
-// TODO: Keep track of progress (e.g. continue where you left off)
-// this can be done by keeping track of the last made shuffle and of which n-value we are at.
-// we do not need to keep track of the rotation buffer, as it will be semi-consistent.
 pub struct WordsList<'a>(pub Vec<WordsEntry<'a>>);
 
 impl WordsList<'_> {
@@ -282,6 +615,46 @@ impl WordsList<'_> {
             self.0.iter_mut().for_each(|x| x.direction = dir);
         }
     }
+
+    /// Formats the list as TSV which can be parsed back by [PrimitiveWordsList], losing all data
+    /// except terms and definitions.
+    pub fn to_tsv(&self) -> String {
+        let args = ParseArgs::default();
+        self.0
+            .iter()
+            .map(|x| {
+                format!(
+                    "{}\t{}\n",
+                    args.join_alternatives(&x.terms, '\t'),
+                    args.join_alternatives(&x.definitions, '\t')
+                )
+            })
+            .collect()
+    }
+
+    /// Takes the entries from `edited` in their order, but keeps the direction and progress of
+    /// every entry whose terms did not change. Entries with the same terms are matched in order.
+    pub fn merge(self, edited: Self) -> Self {
+        let mut old = HashMap::<String, VecDeque<WordsEntry>>::new();
+        for entry in self.0 {
+            old.entry(entry.terms.join(", "))
+                .or_default()
+                .push_back(entry);
+        }
+        let merged = edited.0.into_iter().map(|new| {
+            match old
+                .get_mut(&new.terms.join(", "))
+                .and_then(VecDeque::pop_front)
+            {
+                Some(old) => WordsEntry {
+                    definitions: new.definitions,
+                    ..old
+                },
+                None => new,
+            }
+        });
+        WordsList(merged.collect())
+    }
 }
 
 impl Display for WordsList<'_> {
@@ -310,13 +683,21 @@ impl<'a> From<PrimitiveWordsList<'a>> for WordsList<'a> {
     fn from(input: PrimitiveWordsList<'a>) -> WordsList<'a> {
         WordsList(
             input
-                .0
+                .entries
                 .into_iter()
-                .map(|(term, definitions)| WordsEntry {
-                    terms: vec![Cow::Owned(term)],
-                    definitions,
-                    direction: WordsDirection::Auto,
-                    times_answered_correctly: 0,
+                .map(|entry| {
+                    let (direction, times_answered_correctly) =
+                        entry.progress.unwrap_or((WordsDirection::Auto, 0));
+                    WordsEntry {
+                        terms: entry.terms,
+                        definitions: entry.definitions,
+                        direction,
+                        times_answered_correctly,
+                        times_answered_wrong: 0,
+                        last_seen: None,
+                        review: None,
+                        last_session: None,
+                    }
                 })
                 .collect(),
         )
@@ -343,12 +724,55 @@ impl From<WordsEntry<'_>> for PrintableWordsEntry {
     }
 }
 
+#[serde_as]
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct WordsEntry<'a> {
     pub terms: Vec<Cow<'a, str>>,
     pub definitions: Vec<Cow<'a, str>>,
     pub direction: WordsDirection,
     pub times_answered_correctly: usize,
+    #[serde(default)]
+    pub times_answered_wrong: usize,
+    /// When the entry was last asked, `None` if it has never been practiced (or was practiced
+    /// before this was kept track of)
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Spaced repetition state, `None` if this entry has never been reviewed
+    #[serde(default)]
+    pub review: Option<ReviewState>,
+    /// When the last session the entry was answered in started, and whether it was answered wrong
+    /// in that session, see [crate::practice::wrong_last_session]
+    #[serde_as(as = "Option<(DisplayFromStr, _)>")]
+    #[serde(default)]
+    pub last_session: Option<(DateTime<Utc>, bool)>,
+}
+
+impl WordsEntry<'_> {
+    /// Records an answer to this entry, given in the session which started at `session`
+    pub fn answered(&mut self, correct: bool, now: DateTime<Utc>, session: DateTime<Utc>) {
+        match correct {
+            true => self.times_answered_correctly += 1,
+            false => self.times_answered_wrong += 1,
+        }
+        self.last_seen = Some(now);
+        self.last_session = Some(match self.last_session {
+            Some((x, wrong)) if x == session => (session, wrong || !correct),
+            _ => (session, !correct),
+        });
+    }
+
+    /// The part of the answers which were correct, `None` if it has never been answered
+    pub fn correct_ratio(&self) -> Option<f64> {
+        match self.times_answered_correctly + self.times_answered_wrong {
+            0 => None,
+            total => Some(self.times_answered_correctly as f64 / total as f64),
+        }
+    }
+
+    pub fn is_new(&self) -> bool {
+        self.last_seen.is_none() && self.correct_ratio().is_none()
+    }
 }
 
 #[derive(ValueEnum, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, Tabled)]
@@ -412,34 +836,374 @@ impl FromStr for WordsDirection {
     }
 }
 
-/// File format: KEY<tab/equals>VALUE1<comma/slash>VALUE2
+/// File format: KEY<tab/equals>VALUE1<comma/slash>VALUE2[<tab>DIRECTION<tab>TIMES_CORRECT]
 /// Values are always trimmed when testing for correctness.
 /// Values can optionally be checked for
+/// Values containing separators can be quoted, see [formats::ParseArgs] for everything which can
+/// be changed when importing.
+/// The direction and amount of times answered correctly are only used if both are valid, so they
+/// can be exported and imported again.
 // Ex. (nl -> en): "bank	sofa, bank"
-pub struct PrimitiveWordsList<'a>(HashMap<String, Vec<Cow<'a, str>>>);
+pub struct PrimitiveWordsList<'a> {
+    /// In the order of the file
+    entries: Vec<PrimitiveWordsEntry<'a>>,
+    /// Every term to the first entry which has it
+    terms: HashMap<Cow<'a, str>, usize>,
+    duplicates: Duplicates,
+    /// The lines which were merged or kept while they have a term of an earlier line
+    duplicate_lines: Vec<DuplicateLine>,
+}
+
+/// A line with a term which is already on an earlier line, see [Duplicates]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateLine {
+    pub line: usize,
+    pub earlier: usize,
+    pub terms: String,
+    pub merged: bool,
+}
+
+impl Display for DuplicateLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self.merged {
+            true => "merged them",
+            false => "kept both",
+        };
+        write!(
+            f,
+            "`{}` on line {} is already on line {}, {action}",
+            self.terms, self.line, self.earlier
+        )
+    }
+}
+
+pub struct PrimitiveWordsEntry<'a> {
+    terms: Vec<Cow<'a, str>>,
+    definitions: Vec<Cow<'a, str>>,
+    progress: Option<(WordsDirection, usize)>,
+    /// The line the entry starts on
+    line: usize,
+}
+
+impl<'a> PrimitiveWordsList<'a> {
+    pub fn new(duplicates: Duplicates) -> Self {
+        Self {
+            entries: Vec::new(),
+            terms: HashMap::new(),
+            duplicates,
+            duplicate_lines: Vec::new(),
+        }
+    }
+
+    /// Adds an entry from `line`. If one of its terms is already in the list, it is handled as
+    /// set by [Duplicates] and remembered, see [Self::duplicate_lines].
+    pub fn insert(
+        &mut self,
+        line: usize,
+        terms: Vec<Cow<'a, str>>,
+        definitions: Vec<Cow<'a, str>>,
+        progress: Option<(WordsDirection, usize)>,
+    ) -> Result<(), String> {
+        let existing = terms
+            .iter()
+            .filter_map(|x| self.terms.get(x))
+            .min()
+            .copied();
+        let Some(existing) = existing else {
+            self.push(line, terms, definitions, progress);
+            return Ok(());
+        };
+        let earlier = self.entries[existing].line;
+        let joined = terms.join(", ");
+        match self.duplicates {
+            Duplicates::Merge => {
+                for term in &terms {
+                    self.terms.entry(term.clone()).or_insert(existing);
+                }
+                let entry = &mut self.entries[existing];
+                for (old, new) in [
+                    (&mut entry.terms, terms),
+                    (&mut entry.definitions, definitions),
+                ] {
+                    for x in new {
+                        if !old.contains(&x) {
+                            old.push(x);
+                        }
+                    }
+                }
+                entry.progress = progress.or(entry.progress);
+            }
+            Duplicates::Keep => self.push(line, terms, definitions, progress),
+            Duplicates::Fail => {
+                return Err(format!("`{joined}` is already on line {earlier}"));
+            }
+        }
+        self.duplicate_lines.push(DuplicateLine {
+            line,
+            earlier,
+            terms: joined,
+            merged: self.duplicates == Duplicates::Merge,
+        });
+        Ok(())
+    }
+
+    fn push(
+        &mut self,
+        line: usize,
+        terms: Vec<Cow<'a, str>>,
+        definitions: Vec<Cow<'a, str>>,
+        progress: Option<(WordsDirection, usize)>,
+    ) {
+        for term in &terms {
+            self.terms.entry(term.clone()).or_insert(self.entries.len());
+        }
+        self.entries.push(PrimitiveWordsEntry {
+            terms,
+            definitions,
+            progress,
+            line,
+        });
+    }
+
+    /// The lines which have a term of an earlier line, in the order of the file
+    pub fn duplicate_lines(&self) -> &[DuplicateLine] {
+        &self.duplicate_lines
+    }
+}
 
+/// Parses the optional direction and times correct columns
+pub fn parse_progress(
+    direction: Option<&str>,
+    times: Option<&str>,
+) -> Option<(WordsDirection, usize)> {
+    let direction = direction?.trim().to_ascii_lowercase().parse().ok()?;
+    let times = times?.trim().parse().ok()?;
+    Some((direction, times))
+}
+
+/// Parses TSV with the default [formats::ParseArgs]
 impl<'a> TryFrom<&'a str> for PrimitiveWordsList<'a> {
     type Error = color_eyre::Report;
 
+    /// Parses TSV as written by [WordsList::to_tsv], which has no header
     fn try_from(s: &'a str) -> Result<Self> {
-        let mut map = HashMap::with_capacity(s.split_terminator('\n').count());
-        for (n, line) in s.split_terminator('\n').enumerate() {
-            let mut split = line.split(['\t', '=']);
-            let key = split.next().unwrap().trim();
-            let values = split.next().ok_or_else(|| {
-                eyre!(
-                    "Couldn't parse line number {}: Term needs definition",
-                    n + 1
-                )
-            })?;
-            let mut values = values
-                .split_terminator([',', '/'])
-                .map(|x| Cow::Borrowed(x.trim()))
-                .collect();
-            map.entry(key.to_string())
-                .or_insert_with(Vec::new)
-                .append(&mut values)
+        let args = ParseArgs {
+            header: Header::No,
+            ..Default::default()
+        };
+        formats::parse_tsv(s, &args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn index() -> WordsIndex {
+        let list = |name: &str| WordsMeta::new(name.to_string(), None, None, None);
+        WordsIndex {
+            lists: vec![
+                list("French chapter 1"),
+                list("French chapter 2"),
+                list("German"),
+                list("2"),
+            ],
+            ..Default::default()
         }
-        Ok(Self(map))
+    }
+
+    fn resolve(index: &WordsIndex, handle: &str) -> Option<usize> {
+        index.resolve(&ListHandle(handle.to_string())).ok()
+    }
+
+    #[test]
+    fn test_resolve() {
+        let index = index();
+        assert_eq!(resolve(&index, "2"), Some(2));
+        assert_eq!(resolve(&index, "german"), Some(3));
+        assert_eq!(resolve(&index, "chapter 2"), Some(2));
+        assert_eq!(resolve(&index, "germna"), Some(3));
+        assert_eq!(resolve(&index, "french"), None);
+        assert_eq!(resolve(&index, "spanish"), None);
+
+        let uuid = index.lists[2].uuid.to_string();
+        assert_eq!(resolve(&index, &uuid), Some(3));
+        assert_eq!(resolve(&index, &uuid[..8]), Some(3));
+        assert_eq!(resolve(&index, &uuid.to_uppercase()), Some(3));
+    }
+
+    #[test]
+    fn test_resolve_out_of_range() {
+        let index = index();
+        assert_eq!(resolve(&index, "4"), Some(4));
+        assert_eq!(resolve(&index, "5"), None);
+        assert_eq!(resolve(&index, "0"), None);
+        let error = index.resolve(&ListHandle("12".to_string())).unwrap_err();
+        assert_eq!(error.to_string(), "There is no list with ID 12");
+    }
+
+    #[test]
+    fn test_short_uuid() {
+        let mut index = index();
+        index.lists[2].uuid = Uuid::from_u128(0x00000001_0000_4000_8000_000000000000);
+        let short = index.lists[2].short_uuid();
+        assert_eq!(short, "00000001-");
+        assert_eq!(resolve(&index, &short), Some(3));
+        assert_eq!(index.resolve_exact(&ListHandle(short)).ok(), Some(3));
+        index.lists[1].uuid = Uuid::from_u128(0xa0000001_0000_4000_8000_000000000000);
+        assert_eq!(index.lists[1].short_uuid(), "a0000001");
+    }
+
+    #[test]
+    fn test_resolve_exact() {
+        let index = index();
+        let resolve = |handle: &str| index.resolve_exact(&ListHandle(handle.to_string())).ok();
+        assert_eq!(resolve("3"), Some(3));
+        assert_eq!(resolve("German"), Some(3));
+        assert_eq!(resolve("german"), None);
+        assert_eq!(resolve("chapter 2"), None);
+        assert_eq!(resolve("Germna"), None);
+
+        let uuid = index.lists[2].uuid.to_string();
+        assert_eq!(resolve(&uuid[..8]), Some(3));
+        assert_eq!(resolve(&uuid.to_uppercase()), Some(3));
+    }
+
+    #[test]
+    fn test_lists_with_language() {
+        let tag = |x: &str| x.parse::<LanguageTag>().unwrap();
+        let list = |terms: &str, definition: &str| {
+            WordsMeta::new(
+                format!("{terms}-{definition}"),
+                Some(tag(terms)),
+                Some(tag(definition)),
+                None,
+            )
+        };
+        let mut index = WordsIndex {
+            lists: vec![list("fr", "en"), list("de", "nl"), list("nl", "fr-CA")],
+            ..Default::default()
+        };
+        assert_eq!(index.lists_with_language(&tag("fr")), [1, 3]);
+        assert_eq!(index.lists_with_language(&tag("fr-CA")), [3]);
+        assert_eq!(index.lists_with_language(&tag("german")), [2]);
+        assert!(index.lists_with_language(&tag("spanish")).is_empty());
+
+        index.native_language = Language::Tag(tag("nl"));
+        assert_eq!(index.lists_with_language(&tag("French")), [3, 1]);
+        assert_eq!(index.lists_with_language(&tag("nl")), [2, 3]);
+    }
+
+    #[test]
+    fn test_language_serde() {
+        let language = Language::Tag("nl-BE".parse().unwrap());
+        let ser = ron::ser::to_string(&language).unwrap();
+        assert_eq!(ser, r#"(Some("nl-BE"))"#);
+        assert!(ron::de::from_str::<Language>(&ser).unwrap() == language);
+        assert!(
+            ron::de::from_str::<Language>(r#"(Some("dutch"))"#).unwrap()
+                == Language::Tag("nl".parse().unwrap())
+        );
+        let unparsed = ron::de::from_str::<Language>(r#"(Some("nonsense"))"#).unwrap();
+        assert!(unparsed == Language::Unparsed(String::from("nonsense")));
+        assert_eq!(
+            ron::ser::to_string(&unparsed).unwrap(),
+            r#"(Some("nonsense"))"#
+        );
+        assert!(ron::de::from_str::<Language>("(None)").unwrap() == Language::Unset);
+    }
+
+    #[test]
+    fn test_unparsed_language_survives_save() {
+        // An index as written by the first versions, which stored the languages as plain strings
+        let baseline = r#"(
+    lists: [
+        (
+            name: "old",
+            uuid: "d173295d-b953-4e73-8128-a23e1ee81dcd",
+            terms: (Some("nonsense")),
+            definition: (Some("en")),
+            created_at: "2022-07-01 12:00:00 UTC",
+            last_modified: "2022-07-01 12:00:00 UTC",
+            folder: None,
+            progress: None,
+            shuffle_map: None,
+        ),
+    ],
+)"#;
+        let index = ron::de::from_str::<WordsIndex>(baseline).unwrap();
+        assert!(index.lists[0].terms == Language::Unparsed(String::from("nonsense")));
+        assert!(index.lists[0].definition == Language::Tag("en".parse().unwrap()));
+
+        let dir = TempDir::new("index");
+        let path = dir.join("index.ron");
+        store::write_ron(&path, &index).unwrap();
+        let saved = store::read_ron::<WordsIndex>(&path).unwrap();
+        assert!(saved.lists[0].terms == Language::Unparsed(String::from("nonsense")));
+        assert!(saved.lists[0].definition == index.lists[0].definition);
+    }
+
+    #[test]
+    fn test_migrate_progress() {
+        let baseline = r#"(
+    lists: [
+        (
+            name: "old",
+            uuid: "d173295d-b953-4e73-8128-a23e1ee81dcd",
+            terms: (Some("nl")),
+            definition: (Some("en")),
+            created_at: "2022-07-01 12:00:00 UTC",
+            last_modified: "2022-07-01 12:00:00 UTC",
+            folder: None,
+            progress: Some(2),
+            shuffle_map: None,
+        ),
+    ],
+)"#;
+        let mut index = ron::de::from_str::<WordsIndex>(baseline).unwrap();
+        index.migrate();
+        let session = index.lists[0].session.as_ref().unwrap();
+        assert_eq!(session.learned, Learned::Count(2));
+        assert!(session.rotation.is_empty());
+
+        let saved = ron::ser::to_string(&index).unwrap();
+        assert!(!saved.contains("progress"), "{saved}");
+        let mut loaded = ron::de::from_str::<WordsIndex>(&saved).unwrap();
+        loaded.migrate();
+        assert!(loaded == index);
+    }
+
+    #[test]
+    fn test_remove_uuid() {
+        let mut index = index();
+        let uuid = index.lists[1].uuid;
+        assert!(index.remove_uuid(&uuid).is_some());
+        assert!(index.remove_uuid(&uuid).is_none());
+        assert_eq!(resolve(&index, "french"), Some(1));
+    }
+
+    #[test]
+    fn test_merge_keeps_edited_order() {
+        let parse = |data| WordsList::from(PrimitiveWordsList::try_from(data).unwrap());
+        let mut old = parse("kat\tcat\nhond\tdog\nmuis\tmouse\n");
+        old.0[1].times_answered_correctly = 2;
+        old.0[2].direction = WordsDirection::DT;
+
+        // `muis` was moved to the front, `vis` inserted in the middle and `kat` removed
+        let merged = old.merge(parse("muis\tmice\nvis\tfish\nhond\tdog, hound\n"));
+        let entries = merged
+            .0
+            .iter()
+            .map(|x| (x.terms.join(", "), x.definitions.join(", ")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [("muis", "mice"), ("vis", "fish"), ("hond", "dog, hound")]
+                .map(|(x, y)| (x.to_string(), y.to_string()))
+        );
+        assert_eq!(merged.0[0].direction, WordsDirection::DT);
+        assert_eq!(merged.0[1].times_answered_correctly, 0);
+        assert_eq!(merged.0[2].times_answered_correctly, 2);
     }
 }
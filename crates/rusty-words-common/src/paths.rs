@@ -1,7 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use color_eyre::{Result, eyre::eyre};
-use dirs::data_dir;
+use dirs::{config_dir, data_dir};
 use uuid::Uuid;
 
 pub fn root_dir() -> Result<PathBuf> {
@@ -10,10 +10,30 @@ pub fn root_dir() -> Result<PathBuf> {
         .ok_or_else(|| eyre!("Could not find root dir"))
 }
 
+pub fn config_file() -> Result<PathBuf> {
+    config_dir()
+        .map(|x| x.join("rusty-words").join("config.ron"))
+        .ok_or_else(|| eyre!("Could not find config dir"))
+}
+
 pub fn index_file() -> Result<PathBuf> {
     Ok(root_dir()?.join("index.ron"))
 }
 
+/// Locked while the store is being read or modified, see [crate::store::StoreLock]
+pub fn lock_file() -> Result<PathBuf> {
+    Ok(root_dir()?.join("lock"))
+}
+
+pub fn history_file() -> Result<PathBuf> {
+    Ok(root_dir()?.join("history.log"))
+}
+
+/// See [crate::search::SearchIndex]
+pub fn search_index_file() -> Result<PathBuf> {
+    Ok(root_dir()?.join("search.idx"))
+}
+
 pub fn new_words_file(uuid: &Uuid) -> Result<PathBuf> {
     Ok(root_dir()?.join(format!("{uuid}.ron")))
 }
@@ -0,0 +1,465 @@
+//! Choosing which entries to practice with `try`, see [PracticeMode], and where a session was
+//! left off, see [SessionState].
+
+use std::{collections::HashMap, hash::Hash};
+
+use clap::ValueEnum;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    judgement::TryMethod,
+    model::{WordsDirection, WordsEntry},
+};
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PracticeMode {
+    /// Every entry, continuing where you left off
+    #[default]
+    All,
+    /// The entries which were answered wrong in the last session
+    Mistakes,
+    /// The entries which have been answered before, the lowest ratio of correct answers first
+    Weakest,
+    /// The entries which have never been practiced
+    New,
+}
+
+/// For every entry, whether it was answered wrong in the last session its list was practiced in.
+/// `lists` tells for every entry which list it belongs to.
+pub fn wrong_last_session<T: Eq + Hash>(entries: &[WordsEntry], lists: &[T]) -> Vec<bool> {
+    let mut last = HashMap::new();
+    for (entry, list) in entries.iter().zip(lists) {
+        if let Some((session, _)) = entry.last_session {
+            let last = last.entry(list).or_insert(session);
+            *last = session.max(*last);
+        }
+    }
+    entries
+        .iter()
+        .zip(lists)
+        .map(|(entry, list)| match entry.last_session {
+            Some((session, wrong)) => wrong && last.get(list) == Some(&session),
+            None => false,
+        })
+        .collect()
+}
+
+/// The positions of the entries to practice in `mode`, in the order they should be practiced.
+/// `wrong_last_session` tells for every entry whether it was answered wrong in the last session,
+/// see [wrong_last_session].
+pub fn select(
+    entries: &[WordsEntry],
+    mode: PracticeMode,
+    wrong_last_session: &[bool],
+) -> Vec<usize> {
+    let positions = 0..entries.len();
+    match mode {
+        PracticeMode::All => positions.collect(),
+        PracticeMode::Mistakes => positions.filter(|&i| wrong_last_session[i]).collect(),
+        PracticeMode::New => positions.filter(|&i| entries[i].is_new()).collect(),
+        PracticeMode::Weakest => {
+            let mut weakest = positions
+                .filter_map(|i| Some((entries[i].correct_ratio()?, i)))
+                .collect::<Vec<_>>();
+            // Ties go to the entry which was answered wrong most often
+            weakest.sort_by(|(x, i), (y, j)| {
+                let wrong = |i: &usize| entries[*i].times_answered_wrong;
+                x.total_cmp(y).then(wrong(j).cmp(&wrong(i)))
+            });
+            weakest.into_iter().map(|(_, i)| i).collect()
+        }
+    }
+}
+
+/// Where a `try` session of a list was left off, so it can be resumed exactly. The entries are
+/// stored by their [identity], so the state still applies after the list is changed.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    /// The entries which have been learned
+    pub learned: Learned,
+    /// The entries in rotation, with how many times they were answered correctly
+    pub rotation: Vec<(u64, usize)>,
+    /// What the order of the entries is derived from, see [order]. `None` if the entries are not
+    /// shuffled.
+    pub seed: Option<u64>,
+    pub direction: WordsDirection,
+    pub method: TryMethod,
+}
+
+/// Which entries have been learned in a session
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Learned {
+    /// The [identity] of every learned entry
+    Entries(Vec<u64>),
+    /// Only how many entries have been learned, as stored by older versions. The first entries in
+    /// the order which are not in rotation are taken as learned.
+    Count(usize),
+}
+
+impl Learned {
+    /// How many entries have been learned
+    pub fn count(&self) -> usize {
+        match self {
+            Self::Entries(entries) => entries.len(),
+            Self::Count(count) => *count,
+        }
+    }
+}
+
+impl Serialize for Learned {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Entries(entries) => entries.serialize(serializer),
+            Self::Count(count) => count.serialize(serializer),
+        }
+    }
+}
+
+/// Older versions stored a number instead of the entries
+impl<'de> Deserialize<'de> for Learned {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Learned;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "the learned entries or how many there are")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, count: u64) -> Result<Learned, E> {
+                Ok(Learned::Count(count as usize))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Learned, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(x) = seq.next_element()? {
+                    entries.push(x);
+                }
+                Ok(Learned::Entries(entries))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// A [SessionState] applied to the entries of the list as they are now, in positions
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Resumed {
+    /// The entries in rotation, with how many times they were answered correctly
+    pub rotation: Vec<(usize, usize)>,
+    pub learned: Vec<usize>,
+    /// The entries which still have to be put in rotation, in order
+    pub upcoming: Vec<usize>,
+}
+
+impl SessionState {
+    /// A session at the start of a list
+    pub fn new(seed: Option<u64>, direction: WordsDirection, method: TryMethod) -> Self {
+        Self {
+            learned: Learned::Entries(Vec::new()),
+            rotation: Vec::new(),
+            seed,
+            direction,
+            method,
+        }
+    }
+
+    /// Where to continue in `entries`, with at least `rotation` entries in rotation if there are
+    /// enough left. Entries which were removed from the list since are left out, entries which
+    /// were added are upcoming.
+    pub fn resume(&self, entries: &[WordsEntry], rotation: usize) -> Resumed {
+        let order = order(entries, self.seed);
+        // Entries with the same terms have the same identity, so every position is used only once
+        let mut positions = HashMap::<u64, Vec<usize>>::new();
+        for &i in order.iter().rev() {
+            positions.entry(identity(&entries[i])).or_default().push(i);
+        }
+        let mut take = |identity: &u64| positions.get_mut(identity)?.pop();
+
+        let mut resumed = Resumed {
+            rotation: (self.rotation.iter())
+                .filter_map(|(x, progress)| Some((take(x)?, *progress)))
+                .collect(),
+            learned: match &self.learned {
+                Learned::Entries(entries) => entries.iter().filter_map(&mut take).collect(),
+                Learned::Count(_) => Vec::new(),
+            },
+            upcoming: Vec::new(),
+        };
+        let used = |i: &usize, resumed: &Resumed| {
+            resumed.learned.contains(i) || resumed.rotation.iter().any(|(x, _)| x == i)
+        };
+        if let Learned::Count(count) = self.learned {
+            resumed.learned = (order.iter().copied())
+                .filter(|i| !used(i, &resumed))
+                .take(count)
+                .collect();
+        }
+        let mut upcoming = (order.into_iter())
+            .filter(|i| !used(i, &resumed))
+            .collect::<Vec<_>>()
+            .into_iter();
+        while resumed.rotation.len() < rotation.max(1) {
+            let Some(i) = upcoming.next() else { break };
+            resumed.rotation.push((i, 0));
+        }
+        resumed.upcoming = upcoming.collect();
+        resumed
+    }
+
+    /// The state to resume from later, `rotation` and `learned` are positions in `entries`
+    pub fn left_off(
+        &self,
+        entries: &[WordsEntry],
+        rotation: impl IntoIterator<Item = (usize, usize)>,
+        learned: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        Self {
+            learned: Learned::Entries(learned.into_iter().map(|i| identity(&entries[i])).collect()),
+            rotation: (rotation.into_iter())
+                .map(|(i, progress)| (identity(&entries[i]), progress))
+                .collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// A random number generator which always gives the same numbers for the same seed, on every
+/// platform. Seeded randomly if there is no seed.
+pub fn rng(seed: Option<u64>) -> impl Rng {
+    match seed {
+        Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+        None => ChaCha8Rng::from_entropy(),
+    }
+}
+
+/// The positions of `entries` in the order they are practiced in, shuffled if there is a seed.
+/// Every entry gets a place from the seed and its terms, so editing a list doesn't change the order
+/// of the other entries.
+pub fn order(entries: &[WordsEntry], seed: Option<u64>) -> Vec<usize> {
+    let mut order = (0..entries.len()).collect::<Vec<_>>();
+    if let Some(seed) = seed {
+        // Entries with the same terms keep the order they have in the list
+        order.sort_by_cached_key(|&i| {
+            let place = ChaCha8Rng::seed_from_u64(seed ^ identity(&entries[i])).next_u64();
+            (place, i)
+        });
+    }
+    order
+}
+
+/// A hash (FNV-1a) of the terms of an entry, which unlike [std::hash::DefaultHasher] is the same
+/// in every version
+fn identity(entry: &WordsEntry) -> u64 {
+    entry
+        .terms
+        .iter()
+        .flat_map(|x| x.bytes().chain([0]))
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use chrono::{TimeDelta, Utc};
+
+    use super::*;
+    use crate::model::WordsDirection;
+
+    fn entry(correct: usize, wrong: usize) -> WordsEntry<'static> {
+        WordsEntry {
+            terms: vec![Cow::Borrowed("a")],
+            definitions: vec![Cow::Borrowed("b")],
+            direction: WordsDirection::Auto,
+            times_answered_correctly: correct,
+            times_answered_wrong: wrong,
+            last_seen: (correct + wrong > 0).then(Utc::now),
+            review: None,
+            last_session: None,
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let entries = [
+            entry(3, 1),
+            entry(0, 0),
+            entry(1, 3),
+            entry(2, 2),
+            entry(1, 1),
+        ];
+        let wrong = [false, false, true, true, false];
+        assert_eq!(select(&entries, PracticeMode::All, &wrong), [0, 1, 2, 3, 4]);
+        assert_eq!(select(&entries, PracticeMode::Mistakes, &wrong), [2, 3]);
+        assert_eq!(select(&entries, PracticeMode::New, &wrong), [1]);
+        assert_eq!(
+            select(&entries, PracticeMode::Weakest, &wrong),
+            [2, 3, 4, 0]
+        );
+    }
+
+    fn term(term: &'static str) -> WordsEntry<'static> {
+        WordsEntry {
+            terms: vec![Cow::Borrowed(term)],
+            ..entry(0, 0)
+        }
+    }
+
+    #[test]
+    fn test_session_state() {
+        let entries = ["a", "b", "c", "d", "e"].map(term);
+        let state = SessionState::new(None, WordsDirection::Auto, TryMethod::Write);
+        let resumed = state.resume(&entries, 3);
+        assert_eq!(resumed.rotation, [(0, 0), (1, 0), (2, 0)]);
+        assert!(resumed.learned.is_empty());
+        assert_eq!(resumed.upcoming, [3, 4]);
+
+        let state = SessionState::new(Some(1), WordsDirection::Auto, TryMethod::Write);
+        let resumed = state.resume(&entries, 10);
+        assert_eq!(
+            resumed.rotation,
+            order(&entries, Some(1))
+                .into_iter()
+                .map(|i| (i, 0))
+                .collect::<Vec<_>>()
+        );
+        assert!(resumed.upcoming.is_empty());
+    }
+
+    #[test]
+    fn test_left_off() {
+        let entries = ["a", "b", "c", "d", "e"].map(term);
+        let state = SessionState::new(None, WordsDirection::Auto, TryMethod::Write);
+        let state = state.left_off(&entries, [(3, 1), (2, 0)], [0, 1]);
+        assert_eq!(state.learned.count(), 2);
+        let resumed = state.resume(&entries, 3);
+        assert_eq!(resumed.rotation, [(3, 1), (2, 0), (4, 0)]);
+        assert_eq!(resumed.learned, [0, 1]);
+        assert!(resumed.upcoming.is_empty());
+
+        // Another list with the same length doesn't get the progress of this one
+        let other = ["f", "g", "h", "i", "j"].map(term);
+        let resumed = state.resume(&other, 3);
+        assert!(resumed.learned.is_empty());
+        assert_eq!(resumed.rotation, [(0, 0), (1, 0), (2, 0)]);
+
+        // Entries with the same terms are told apart
+        let same = ["a", "a", "b"].map(term);
+        let state = SessionState::new(None, WordsDirection::Auto, TryMethod::Write);
+        let resumed = state.left_off(&same, [(1, 2)], [0]).resume(&same, 1);
+        assert_eq!(resumed.rotation, [(0, 2)]);
+        assert_eq!(resumed.learned, [1]);
+        assert_eq!(resumed.upcoming, [2]);
+    }
+
+    #[test]
+    fn test_resume_after_edit() {
+        let entries = ["a", "b", "c", "d", "e"].map(term);
+        let state = SessionState::new(None, WordsDirection::Auto, TryMethod::Write);
+        let state = state.left_off(&entries, [(2, 1), (3, 2)], [0, 1]);
+
+        // `b` and `d` were removed, `x` was added at the front and the rest moved
+        let edited = ["x", "e", "c", "a"].map(term);
+        let resumed = state.resume(&edited, 2);
+        assert_eq!(resumed.learned, [3]);
+        assert_eq!(resumed.rotation, [(2, 1), (0, 0)]);
+        assert_eq!(resumed.upcoming, [1]);
+    }
+
+    #[test]
+    fn test_learned_count() {
+        let entries = ["a", "b", "c", "d", "e"].map(term);
+        let state = SessionState {
+            learned: Learned::Count(2),
+            ..SessionState::new(None, WordsDirection::Auto, TryMethod::Write)
+        };
+        let resumed = state.resume(&entries, 2);
+        assert_eq!(resumed.learned, [0, 1]);
+        assert_eq!(resumed.rotation, [(2, 0), (3, 0)]);
+        assert_eq!(resumed.upcoming, [4]);
+
+        assert_eq!(
+            ron::de::from_str::<Learned>("3").unwrap(),
+            Learned::Count(3)
+        );
+        let learned = Learned::Entries(vec![1, u64::MAX]);
+        let ser = ron::ser::to_string(&learned).unwrap();
+        assert_eq!(ron::de::from_str::<Learned>(&ser).unwrap(), learned);
+    }
+
+    #[test]
+    fn test_order() {
+        let entries = ["a", "b", "c", "d", "e", "f", "g", "h"].map(term);
+        let shuffled = order(&entries, Some(42));
+        assert_eq!(shuffled, order(&entries, Some(42)));
+        assert_ne!(shuffled, order(&entries, Some(43)));
+        let mut sorted = shuffled.clone();
+        sorted.sort();
+        assert_eq!(sorted, order(&entries, None));
+
+        // Removing an entry doesn't change the order of the others
+        let terms = |entries: &[WordsEntry<'static>], order: Vec<usize>| {
+            order
+                .into_iter()
+                .map(|i| entries[i].terms[0].clone())
+                .filter(|x| x != "c")
+                .collect::<Vec<_>>()
+        };
+        let without_c = entries
+            .iter()
+            .filter(|x| x.terms[0] != "c")
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            terms(&entries, shuffled),
+            terms(&without_c, order(&without_c, Some(42)))
+        );
+    }
+
+    #[test]
+    fn test_answered() {
+        let mut entry = entry(0, 0);
+        assert!(entry.is_new());
+        let session = Utc::now();
+        entry.answered(false, Utc::now(), session);
+        entry.answered(true, Utc::now(), session);
+        assert_eq!(entry.correct_ratio(), Some(0.5));
+        assert!(!entry.is_new());
+        assert_eq!(entry.last_session, Some((session, true)));
+        let later = session + TimeDelta::minutes(5);
+        entry.answered(true, Utc::now(), later);
+        assert_eq!(entry.last_session, Some((later, false)));
+    }
+
+    #[test]
+    fn test_wrong_last_session() {
+        let session = Utc::now();
+        let later = session + TimeDelta::minutes(5);
+        let answered = |last_session| WordsEntry {
+            last_session,
+            ..entry(1, 1)
+        };
+        let entries = [
+            answered(Some((session, true))),
+            answered(Some((later, true))),
+            answered(Some((later, false))),
+            answered(None),
+            // Another list, whose last session was earlier
+            answered(Some((session, true))),
+        ];
+        assert_eq!(
+            wrong_last_session(&entries, &[1, 1, 1, 1, 2]),
+            [false, true, false, false, true]
+        );
+    }
+}
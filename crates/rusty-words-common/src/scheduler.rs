@@ -0,0 +1,152 @@
+//! Spaced repetition, using the SM-2 algorithm.
+//!
+//! Every entry which has been reviewed at least once gets a [ReviewState], which is stored in the
+//! list file. The state keeps track of when the entry should be reviewed again, and how easy it
+//! has been to remember. Entries which have not been reviewed yet are considered new.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+use crate::model::WordsList;
+
+/// Ease factors are stored in thousandths, so 2500 means an ease of 2.5
+pub const DEFAULT_EASE: u32 = 2500;
+pub const MINIMUM_EASE: u32 = 1300;
+
+/// Quality of a correct answer, "correct response after a hesitation"
+pub const QUALITY_CORRECT: u8 = 4;
+/// Quality of a wrong answer, "incorrect response; the correct one remembered"
+pub const QUALITY_WRONG: u8 = 1;
+/// Quality of a correct answer after using hints, "correct response recalled with serious
+/// difficulty"
+pub const QUALITY_HINTED: u8 = 3;
+
+#[serde_as]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ReviewState {
+    /// Ease factor in thousandths
+    pub ease: u32,
+    /// Amount of days until the next review
+    pub interval: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub due: DateTime<Utc>,
+    /// How many times the entry was forgotten after having been learned
+    pub lapses: u32,
+    /// Amount of correct reviews in a row
+    pub repetitions: u32,
+}
+
+impl ReviewState {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            ease: DEFAULT_EASE,
+            interval: 0,
+            due: now,
+            lapses: 0,
+            repetitions: 0,
+        }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.due <= now
+    }
+
+    /// Schedules the next review given the quality of the answer, from 0 (complete blackout) to 5
+    /// (perfect response).
+    pub fn review(&mut self, quality: u8, now: DateTime<Utc>) {
+        let quality = quality.min(5);
+        if quality >= 3 {
+            self.interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval as u64 * self.ease as u64).div_ceil(1000) as u32,
+            };
+            self.repetitions += 1;
+        } else {
+            if self.repetitions > 0 {
+                self.lapses += 1;
+            }
+            self.repetitions = 0;
+            self.interval = 1;
+        }
+
+        // EF' = EF + (0.1 - (5 - q) * (0.08 + (5 - q) * 0.02)), in thousandths
+        let q = 5 - quality as i64;
+        let ease = self.ease as i64 + 100 - q * (80 + q * 20);
+        self.ease = ease.max(MINIMUM_EASE as i64) as u32;
+        self.due = now + Duration::days(self.interval as i64);
+    }
+}
+
+/// Returns the indices of the entries which have been reviewed before and are due, most overdue
+/// first.
+pub fn due_entries(list: &WordsList, now: DateTime<Utc>) -> Vec<usize> {
+    let mut due: Vec<_> = list
+        .0
+        .iter()
+        .enumerate()
+        .filter_map(|(i, x)| x.review.as_ref().filter(|r| r.is_due(now)).map(|r| (i, r)))
+        .collect();
+    due.sort_by_key(|(_, r)| r.due);
+    due.into_iter().map(|(i, _)| i).collect()
+}
+
+/// Returns the indices of the entries which have never been reviewed
+pub fn new_entries(list: &WordsList) -> Vec<usize> {
+    list.0
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| x.review.is_none())
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intervals() {
+        let now = Utc::now();
+        let mut state = ReviewState::new(now);
+        assert!(state.is_due(now));
+
+        state.review(QUALITY_CORRECT, now);
+        assert_eq!(state.interval, 1);
+        state.review(QUALITY_CORRECT, now);
+        assert_eq!(state.interval, 6);
+        state.review(QUALITY_CORRECT, now);
+        assert_eq!(state.interval, 15);
+        assert_eq!(state.repetitions, 3);
+        assert_eq!(state.ease, DEFAULT_EASE);
+        assert!(!state.is_due(now));
+        assert!(state.is_due(now + Duration::days(15)));
+    }
+
+    #[test]
+    fn test_lapse() {
+        let now = Utc::now();
+        let mut state = ReviewState::new(now);
+        state.review(QUALITY_WRONG, now);
+        assert_eq!(state.lapses, 0);
+        assert_eq!(state.interval, 1);
+
+        state.review(QUALITY_CORRECT, now);
+        state.review(QUALITY_CORRECT, now);
+        state.review(QUALITY_WRONG, now);
+        assert_eq!(state.lapses, 1);
+        assert_eq!(state.repetitions, 0);
+        assert_eq!(state.interval, 1);
+    }
+
+    #[test]
+    fn test_minimum_ease() {
+        let now = Utc::now();
+        let mut state = ReviewState::new(now);
+        for _ in 0..20 {
+            state.review(0, now);
+        }
+        assert_eq!(state.ease, MINIMUM_EASE);
+    }
+}
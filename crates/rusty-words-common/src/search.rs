@@ -0,0 +1,377 @@
+//! Searching the entries of all lists.
+//!
+//! Without an index every list has to be loaded, which gets slow with thousands of lists. The
+//! optional [SearchIndex] is an inverted index: it maps every word, and every trigram of those
+//! words for queries which are only part of a word, to the lists containing it. The candidates for
+//! a query are looked up once, so only the lists which can contain a match are loaded. It is
+//! stored in the store as `search.idx` and is brought up to date with the list files (by their
+//! modification time and size) every time it is used.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::SystemTime,
+};
+
+use color_eyre::{Result, eyre::Context};
+use lazy_regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use uuid::Uuid;
+
+use crate::{
+    judgement::{Strictness, normalise},
+    model::{WordsEntry, WordsIndex, WordsList},
+    paths::{new_words_file, search_index_file},
+    store,
+};
+
+/// Which side of the entries to search
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum SearchField {
+    #[default]
+    Both,
+    Terms,
+    Definitions,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// Case sensitive substring
+    Exact(String),
+    /// Substring, ignoring case and diacritics, see [Strictness::Lenient]
+    Normalised(String),
+    /// The regex is matched against the normalised text if `normalised` is set
+    Regex { regex: Regex, normalised: bool },
+}
+
+#[derive(Debug, Clone)]
+pub struct Query {
+    matcher: Matcher,
+    field: SearchField,
+}
+
+impl Query {
+    pub fn new(query: &str, field: SearchField, regex: bool, normalised: bool) -> Result<Self> {
+        let matcher = match (regex, normalised) {
+            (true, _) => Matcher::Regex {
+                regex: Regex::new(query)
+                    .with_context(|| format!("while parsing the regex `{query}`"))?,
+                normalised,
+            },
+            (false, true) => Matcher::Normalised(normalise(query, Strictness::Lenient)),
+            (false, false) => Matcher::Exact(query.to_string()),
+        };
+        Ok(Self { matcher, field })
+    }
+
+    fn matches_text(&self, text: &str) -> bool {
+        match &self.matcher {
+            Matcher::Exact(query) => text.contains(query.as_str()),
+            Matcher::Normalised(query) => normalise(text, Strictness::Lenient).contains(query),
+            Matcher::Regex { regex, normalised } => match normalised {
+                true => regex.is_match(&normalise(text, Strictness::Lenient)),
+                false => regex.is_match(text),
+            },
+        }
+    }
+
+    pub fn matches(&self, entry: &WordsEntry) -> bool {
+        let terms = entry.terms.iter();
+        let definitions = entry.definitions.iter();
+        match self.field {
+            SearchField::Both => terms.chain(definitions).any(|x| self.matches_text(x)),
+            SearchField::Terms => terms.into_iter().any(|x| self.matches_text(x)),
+            SearchField::Definitions => definitions.into_iter().any(|x| self.matches_text(x)),
+        }
+    }
+
+    /// The positions of the matching entries in `list`
+    pub fn search(&self, list: &WordsList) -> Vec<usize> {
+        list.0
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| self.matches(x))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The words every matching list must contain a word containing, or `None` if the index can't
+    /// be used for this query
+    fn words(&self) -> Option<Vec<String>> {
+        match &self.matcher {
+            Matcher::Exact(query) | Matcher::Normalised(query) => Some(words(query)),
+            Matcher::Regex { .. } => None,
+        }
+    }
+}
+
+/// The normalised words in `text`, a match of a [Query] contains (parts of) the same words
+fn words(text: &str) -> Vec<String> {
+    normalise(text, Strictness::Lenient)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[serde_as]
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    #[serde_as(as = "BTreeMap<DisplayFromStr, _>")]
+    lists: BTreeMap<Uuid, IndexedList>,
+    /// Every word to the lists containing it
+    #[serde_as(as = "BTreeMap<_, BTreeSet<DisplayFromStr>>")]
+    words: BTreeMap<String, BTreeSet<Uuid>>,
+    /// Every trigram of the words to the lists containing it, for looking up parts of words
+    #[serde_as(as = "BTreeMap<_, BTreeSet<DisplayFromStr>>")]
+    trigrams: BTreeMap<String, BTreeSet<Uuid>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+struct IndexedList {
+    /// The modification time and size of the list file when it was indexed
+    modified: SystemTime,
+    size: u64,
+}
+
+/// The sequences of three characters in `word`
+fn trigrams(word: &str) -> impl Iterator<Item = String> + '_ {
+    let chars = word.chars().collect::<Vec<_>>();
+    (0..chars.len().saturating_sub(2)).map(move |i| chars[i..i + 3].iter().collect())
+}
+
+impl SearchIndex {
+    /// Loads the index from the store. The index can always be rebuilt, so a missing or corrupt
+    /// index is replaced by an empty one.
+    pub fn load() -> Result<Self> {
+        let path = search_index_file()?;
+        Ok(store::read_ron(&path).unwrap_or_default())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        store::write_ron(&search_index_file()?, self)
+    }
+
+    /// Indexes the lists which were added or changed since the last update and forgets the lists
+    /// which were removed. Returns whether anything changed.
+    ///
+    /// A list file which can't be read is indexed without any words and a warning is printed, it
+    /// is indexed again once the file changes.
+    pub fn update(&mut self, index: &WordsIndex) -> Result<bool> {
+        let removed = self
+            .lists
+            .keys()
+            .filter(|uuid| !index.lists.iter().any(|x| x.uuid == **uuid))
+            .copied()
+            .collect::<Vec<_>>();
+        let mut changed = !removed.is_empty();
+        for uuid in removed {
+            self.remove(&uuid);
+        }
+
+        for meta in &index.lists {
+            let path = new_words_file(&meta.uuid)?;
+            let Ok(metadata) = path.metadata() else {
+                changed |= self.remove(&meta.uuid);
+                continue;
+            };
+            let (modified, size) = (metadata.modified()?, metadata.len());
+            if self
+                .lists
+                .get(&meta.uuid)
+                .is_some_and(|x| x.modified == modified && x.size == size)
+            {
+                continue;
+            }
+            let list = match store::read_ron::<WordsList>(&path) {
+                Ok(list) => list,
+                Err(e) => {
+                    eprintln!(
+                        "Warning: couldn't index list `{}` ({}), so it isn't searched: {e:#}",
+                        meta.name, meta.uuid
+                    );
+                    WordsList(Vec::new())
+                }
+            };
+            self.insert(meta.uuid, modified, size, &list);
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// Removes a list from the index, returns whether it was in there
+    fn remove(&mut self, uuid: &Uuid) -> bool {
+        if self.lists.remove(uuid).is_none() {
+            return false;
+        }
+        for postings in [&mut self.words, &mut self.trigrams] {
+            postings.retain(|_, lists| {
+                lists.remove(uuid);
+                !lists.is_empty()
+            });
+        }
+        true
+    }
+
+    fn insert(&mut self, uuid: Uuid, modified: SystemTime, size: u64, list: &WordsList) {
+        self.remove(&uuid);
+        let words = list
+            .0
+            .iter()
+            .flat_map(|x| x.terms.iter().chain(x.definitions.iter()))
+            .flat_map(|x| words(x))
+            .collect::<BTreeSet<_>>();
+        for word in words {
+            for trigram in trigrams(&word) {
+                self.trigrams.entry(trigram).or_default().insert(uuid);
+            }
+            self.words.entry(word).or_default().insert(uuid);
+        }
+        self.lists.insert(uuid, IndexedList { modified, size });
+    }
+
+    /// The indexed lists which have a word containing `part`
+    fn lists_with(&self, part: &str) -> BTreeSet<Uuid> {
+        if part.chars().nth(2).is_none() {
+            // Too short for the trigrams, so look through all words
+            return self
+                .words
+                .iter()
+                .filter(|(word, _)| word.contains(part))
+                .flat_map(|(_, lists)| lists.iter().copied())
+                .collect();
+        }
+        let mut found: Option<BTreeSet<Uuid>> = None;
+        for trigram in trigrams(part) {
+            let lists = self.trigrams.get(&trigram).cloned().unwrap_or_default();
+            found = Some(match found {
+                Some(found) => found.intersection(&lists).copied().collect(),
+                None => lists,
+            });
+        }
+        found.unwrap_or_default()
+    }
+
+    /// The lists which can contain a match for `query`
+    pub fn candidates(&self, query: &Query) -> Candidates<'_> {
+        let lists = query.words().map(|words| {
+            let mut found: Option<BTreeSet<Uuid>> = None;
+            for word in words {
+                let lists = self.lists_with(&word);
+                found = Some(match found {
+                    Some(found) => found.intersection(&lists).copied().collect(),
+                    None => lists,
+                });
+            }
+            // A query without any words can match anything
+            found.unwrap_or_else(|| self.lists.keys().copied().collect())
+        });
+        Candidates { index: self, lists }
+    }
+}
+
+/// The lists which can contain a match for a query, see [SearchIndex::candidates]
+pub struct Candidates<'a> {
+    index: &'a SearchIndex,
+    /// `None` if the index can't be used for the query
+    lists: Option<BTreeSet<Uuid>>,
+}
+
+impl Candidates<'_> {
+    /// Whether the list with `uuid` can contain a match. Lists which are not in the index can.
+    pub fn may_match(&self, uuid: &Uuid) -> bool {
+        !self.index.lists.contains_key(uuid) || self.lists.as_ref().is_none_or(|x| x.contains(uuid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{PrimitiveWordsList, WordsList};
+
+    fn list(tsv: &str) -> WordsList<'_> {
+        WordsList::from(PrimitiveWordsList::try_from(tsv).unwrap())
+    }
+
+    fn query(query: &str, field: SearchField, regex: bool, normalised: bool) -> Query {
+        Query::new(query, field, regex, normalised).unwrap()
+    }
+
+    #[test]
+    fn test_matches() {
+        let list = list("het Huis\tthe house\n");
+        let entry = &list.0[0];
+        assert!(query("Huis", SearchField::Both, false, false).matches(entry));
+        assert!(!query("huis", SearchField::Both, false, false).matches(entry));
+        assert!(query("HÚIS", SearchField::Both, false, true).matches(entry));
+        assert!(!query("house", SearchField::Terms, false, false).matches(entry));
+        assert!(query("house", SearchField::Definitions, false, false).matches(entry));
+        assert!(query("^het h", SearchField::Terms, true, true).matches(entry));
+        assert!(!query("^het h", SearchField::Terms, true, false).matches(entry));
+        assert!(Query::new("(", SearchField::Both, true, false).is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut index = SearchIndex::default();
+        let uuid = Uuid::from_u128(1);
+        index.insert(uuid, SystemTime::now(), 0, &list("de Straße\tthe street\n"));
+
+        let may_match = |x: &str, regex: bool, normalised: bool| {
+            index
+                .candidates(&query(x, SearchField::Both, regex, normalised))
+                .may_match(&uuid)
+        };
+        assert!(may_match("stras", false, true));
+        assert!(may_match("de str", false, false));
+        assert!(may_match("e s", false, false));
+        assert!(may_match("reet", false, false));
+        assert!(!may_match("house", false, false));
+        assert!(!may_match("the house", false, false));
+        assert!(!may_match("x", false, false));
+        // The index can't tell for regexes or lists it doesn't know
+        assert!(may_match("house", true, false));
+        assert!(
+            index
+                .candidates(&query("house", SearchField::Both, false, false))
+                .may_match(&Uuid::nil())
+        );
+    }
+
+    #[test]
+    fn test_postings() {
+        let mut index = SearchIndex::default();
+        let (house, street) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        index.insert(house, SystemTime::now(), 0, &list("het huis\tthe house\n"));
+        index.insert(
+            street,
+            SystemTime::now(),
+            0,
+            &list("de straat\tthe street\n"),
+        );
+        assert_eq!(index.words["the"], BTreeSet::from([house, street]));
+        assert_eq!(index.words["huis"], BTreeSet::from([house]));
+        assert_eq!(index.trigrams["tre"], BTreeSet::from([street]));
+
+        fn candidates(index: &SearchIndex, x: &str) -> BTreeSet<Uuid> {
+            index
+                .candidates(&query(x, SearchField::Both, false, false))
+                .lists
+                .unwrap()
+        }
+        assert_eq!(candidates(&index, "the"), BTreeSet::from([house, street]));
+        assert_eq!(candidates(&index, "ous"), BTreeSet::from([house]));
+        assert_eq!(candidates(&index, "ee"), BTreeSet::from([street]));
+        assert_eq!(candidates(&index, "the str"), BTreeSet::from([street]));
+        assert!(candidates(&index, "huis straat").is_empty());
+
+        // Indexing a list again replaces its words
+        index.insert(house, SystemTime::now(), 0, &list("de boom\tthe tree\n"));
+        assert!(!index.words.contains_key("huis"));
+        assert_eq!(candidates(&index, "tre"), BTreeSet::from([house, street]));
+        assert!(index.remove(&street));
+        assert!(!index.words.contains_key("straat"));
+        assert_eq!(candidates(&index, "the"), BTreeSet::from([house]));
+        assert!(!index.remove(&street));
+    }
+}
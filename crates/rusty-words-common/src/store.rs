@@ -0,0 +1,182 @@
+//! Reading and writing the files in the store.
+//!
+//! Every write goes to a temporary file next to the destination first, which is synced to disk and
+//! then renamed over the destination. A crash or a full disk therefore leaves either the old or
+//! the new file behind, never a truncated one.
+//!
+//! Processes which load the index and save it again must hold a [StoreLock], so they don't
+//! overwrite each other's changes.
+
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{Result, eyre::Context};
+use ron::ser::PrettyConfig;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::paths::lock_file;
+
+fn with_extension_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// The temporary file a write to `path` goes through, e.g. `index.ron.tmp`
+pub fn temp_file(path: &Path) -> PathBuf {
+    with_extension_suffix(path, ".tmp")
+}
+
+/// The backup of the previous version of `path`, e.g. `index.ron.bak`
+pub fn backup_file(path: &Path) -> PathBuf {
+    with_extension_suffix(path, ".bak")
+}
+
+/// Atomically replaces the contents of `path`
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let temp = temp_file(path);
+    let result = (|| -> Result<()> {
+        let mut file = File::create(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result.with_context(|| format!("while writing {}", path.display()))?;
+
+    // Make sure the rename itself has hit the disk as well
+    if let Some(parent) = path.parent().filter(|x| !x.as_os_str().is_empty()) {
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Atomically replaces `path` with `value` as pretty RON
+pub fn write_ron<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    let ser = ron::ser::to_string_pretty(value, PrettyConfig::default())?;
+    write_atomic(path, ser.as_bytes())
+}
+
+pub fn read_ron<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    ron::de::from_reader(File::open(path)?)
+        .with_context(|| format!("while reading {}", path.display()))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LockKind {
+    /// For commands which only read the store, any amount of processes can hold it at once
+    Shared,
+    /// For commands which modify the store
+    Exclusive,
+}
+
+/// An advisory lock on the whole store, released when dropped
+pub struct StoreLock {
+    _file: File,
+}
+
+impl StoreLock {
+    /// Locks the store, waiting for other processes to release it if necessary
+    pub fn acquire(kind: LockKind) -> Result<Self> {
+        let path = lock_file()?;
+        if let Some(lock) = Self::try_acquire_at(&path, kind)? {
+            return Ok(lock);
+        }
+        eprintln!("Another rwds-cli process is using the words store, waiting for it to finish...");
+        let file = Self::open(&path)?;
+        loop {
+            match flock(&file, kind, true) {
+                Ok(()) => return Ok(Self { _file: file }),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e).context("while locking the words store"),
+            }
+        }
+    }
+
+    /// Locks the store using the lock file at `path`, or returns `None` if another process holds
+    /// a conflicting lock
+    pub fn try_acquire_at(path: &Path, kind: LockKind) -> Result<Option<Self>> {
+        let file = Self::open(path)?;
+        match flock(&file, kind, false) {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e).context("while locking the words store"),
+        }
+    }
+
+    fn open(path: &Path) -> Result<File> {
+        File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .with_context(|| format!("while opening the lock file {}", path.display()))
+    }
+}
+
+#[cfg(unix)]
+fn flock(file: &File, kind: LockKind, wait: bool) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let mut operation = match kind {
+        LockKind::Shared => libc::LOCK_SH,
+        LockKind::Exclusive => libc::LOCK_EX,
+    };
+    if !wait {
+        operation |= libc::LOCK_NB;
+    }
+    match unsafe { libc::flock(file.as_raw_fd(), operation) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// The store isn't locked on other platforms, so concurrent processes can overwrite each other's
+/// changes there
+#[cfg(not(unix))]
+fn flock(_file: &File, _kind: LockKind, _wait: bool) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = TempDir::new("store");
+        let path = dir.join("list.ron");
+
+        write_ron(&path, &vec![1, 2, 3]).unwrap();
+        write_ron(&path, &vec![4, 5]).unwrap();
+        assert_eq!(read_ron::<Vec<i32>>(&path).unwrap(), [4, 5]);
+        assert!(!temp_file(&path).exists());
+        assert_eq!(backup_file(&path), dir.join("list.ron.bak"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_lock() {
+        let dir = TempDir::new("lock");
+        let path = dir.join("lock");
+        let try_lock = |kind| StoreLock::try_acquire_at(&path, kind).unwrap();
+
+        let shared = try_lock(LockKind::Shared).unwrap();
+        assert!(try_lock(LockKind::Shared).is_some());
+        assert!(try_lock(LockKind::Exclusive).is_none());
+        drop(shared);
+
+        let exclusive = try_lock(LockKind::Exclusive).unwrap();
+        assert!(try_lock(LockKind::Shared).is_none());
+        drop(exclusive);
+        assert!(try_lock(LockKind::Exclusive).is_some());
+    }
+}
@@ -1,24 +1,102 @@
-// // //! From https://lhncbc.nlm.nih.gov/LSG/Projects/lvg/current/docs/designDoc/UDF/unicode/DefaultTables/symbolTable.html
-// // pub const SYMBOL_SEARCHES: &[char] = &[
-// //     '\u{00AB}', '\u{00AD}', '\u{00B4}', '\u{00BB}', '\u{00F7}', '\u{01C0}', '\u{01C3}', '\u{02B9}',
-// //     '\u{02BA}', '\u{02BC}', '\u{02C4}', '\u{02C6}', '\u{02C8}', '\u{02CB}', '\u{02CD}', '\u{02DC}',
-// //     '\u{0300}', '\u{0301}', '\u{0302}', '\u{0303}', '\u{030B}', '\u{030E}', '\u{0331}', '\u{0332}',
-// //     '\u{0338}', '\u{0589}', '\u{05C0}', '\u{05C3}', '\u{066A}', '\u{066D}', '\u{200B}', '\u{2010}',
-// //     '\u{2011}', '\u{2012}', '\u{2013}', '\u{2014}', '\u{2015}', '\u{2016}', '\u{2017}', '\u{2018}',
-// //     '\u{2019}', '\u{201A}', '\u{201B}', '\u{201C}', '\u{201D}', '\u{201E}', '\u{201F}', '\u{2032}',
-// //     '\u{2033}', '\u{2034}', '\u{2035}', '\u{2036}', '\u{2037}', '\u{2038}', '\u{2039}', '\u{203A}',
-// //     '\u{203D}', '\u{2044}', '\u{204E}', '\u{2052}', '\u{2053}', '\u{2060}', '\u{20E5}', '\u{2212}',
-// //     '\u{2215}', '\u{2216}', '\u{2217}', '\u{2223}', '\u{2236}', '\u{223C}', '\u{2264}', '\u{2265}',
-// //     '\u{2266}', '\u{2267}', '\u{2303}', '\u{2329}', '\u{232A}', '\u{266F}', '\u{2731}', '\u{2758}',
-// //     '\u{2762}', '\u{27E6}', '\u{27E8}', '\u{27E9}', '\u{2983}', '\u{2984}', '\u{3003}', '\u{3008}',
-// //     '\u{3009}', '\u{301B}', '\u{301C}', '\u{301D}', '\u{301E}', '\u{FEFF}',
-// // ];
-// //
-// const SYMBOL_REPLACES: &[char] = &[
-//     '"', '-', '\'', '"', '/', '|', '!', '\'', '"', '\'', '^', '^', '\'', '`', '_', '~', '`', '\'',
-//     '^', '~', '"', '"', '_', '_', '/', ':', '|', ':', '%', '*', ' ', '-', '-', '-', '-', '-', '-',
-//     '|', '_', '\'', '\'', ',', '\'', '"', '"', '"', '"', '\'', '"', '\'', '`', '"', '\'', '^', '<',
-//     '>', '?', '/', '*', '%', '~', ' ', '\\', '-', '/', '\\', '*', '|', ':', '~', '<', '>', '<',
-//     '>', '^', '<', '>', '#', '*', '|', '!', '[', '<', '>', '{', '}', '"', '<', '>', ']', '~', '"',
-//     '"',
-// ];
+//! Based on https://lhncbc.nlm.nih.gov/LSG/Projects/lvg/current/docs/designDoc/UDF/unicode/DefaultTables/symbolTable.html
+//! Combining characters are left out, they are handled by stripping diacritics instead.
+
+/// Symbols which look like an ASCII character, and the character they should be folded into
+pub const SYMBOL_TABLE: &[(char, char)] = &[
+    ('\u{00AB}', '"'),
+    ('\u{00B4}', '\''),
+    ('\u{00BB}', '"'),
+    ('\u{00F7}', '/'),
+    ('\u{01C0}', '|'),
+    ('\u{01C3}', '!'),
+    ('\u{02B9}', '\''),
+    ('\u{02BA}', '"'),
+    ('\u{02BC}', '\''),
+    ('\u{02C4}', '^'),
+    ('\u{02C6}', '^'),
+    ('\u{02C8}', '\''),
+    ('\u{02CB}', '`'),
+    ('\u{02CD}', '_'),
+    ('\u{02DC}', '~'),
+    ('\u{0589}', ':'),
+    ('\u{05C0}', '|'),
+    ('\u{05C3}', ':'),
+    ('\u{066A}', '%'),
+    ('\u{066D}', '*'),
+    ('\u{2010}', '-'),
+    ('\u{2011}', '-'),
+    ('\u{2012}', '-'),
+    ('\u{2013}', '-'),
+    ('\u{2014}', '-'),
+    ('\u{2015}', '-'),
+    ('\u{2016}', '|'),
+    ('\u{2017}', '_'),
+    ('\u{2018}', '\''),
+    ('\u{2019}', '\''),
+    ('\u{201A}', ','),
+    ('\u{201B}', '\''),
+    ('\u{201C}', '"'),
+    ('\u{201D}', '"'),
+    ('\u{201E}', '"'),
+    ('\u{201F}', '"'),
+    ('\u{2032}', '\''),
+    ('\u{2033}', '"'),
+    ('\u{2035}', '`'),
+    ('\u{2036}', '"'),
+    ('\u{2038}', '^'),
+    ('\u{2039}', '<'),
+    ('\u{203A}', '>'),
+    ('\u{203D}', '?'),
+    ('\u{2044}', '/'),
+    ('\u{204E}', '*'),
+    ('\u{2052}', '%'),
+    ('\u{2053}', '~'),
+    ('\u{2212}', '-'),
+    ('\u{2215}', '/'),
+    ('\u{2216}', '\\'),
+    ('\u{2217}', '*'),
+    ('\u{2223}', '|'),
+    ('\u{2236}', ':'),
+    ('\u{223C}', '~'),
+    ('\u{2264}', '<'),
+    ('\u{2265}', '>'),
+    ('\u{2266}', '<'),
+    ('\u{2267}', '>'),
+    ('\u{2303}', '^'),
+    ('\u{2329}', '<'),
+    ('\u{232A}', '>'),
+    ('\u{266F}', '#'),
+    ('\u{2731}', '*'),
+    ('\u{2758}', '|'),
+    ('\u{2762}', '!'),
+    ('\u{27E6}', '['),
+    ('\u{27E7}', ']'),
+    ('\u{27E8}', '<'),
+    ('\u{27E9}', '>'),
+    ('\u{2983}', '{'),
+    ('\u{2984}', '}'),
+    ('\u{3003}', '"'),
+    ('\u{3008}', '<'),
+    ('\u{3009}', '>'),
+    ('\u{301A}', '['),
+    ('\u{301B}', ']'),
+    ('\u{301C}', '~'),
+    ('\u{301D}', '"'),
+    ('\u{301E}', '"'),
+];
+
+/// Invisible characters which are removed entirely
+pub const SYMBOL_REMOVALS: &[char] = &['\u{00AD}', '\u{200B}', '\u{2060}', '\u{FEFF}'];
+
+/// Folds a symbol into its ASCII look-alike, or `None` if it should be removed
+pub fn fold_symbol(c: char) -> Option<char> {
+    if SYMBOL_REMOVALS.contains(&c) {
+        return None;
+    }
+    Some(
+        SYMBOL_TABLE
+            .iter()
+            .find(|(search, _)| *search == c)
+            .map_or(c, |(_, replace)| *replace),
+    )
+}
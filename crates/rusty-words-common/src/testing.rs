@@ -0,0 +1,28 @@
+//! Helpers for the tests of this crate
+
+use std::path::{Path, PathBuf};
+
+/// A directory for a test in the temporary directory, which is removed when it is dropped, also
+/// when the test fails
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates an empty directory, unique to `name` and this process. A directory left behind by
+    /// an earlier process with the same ID is removed first.
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("rusty-words-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}